@@ -0,0 +1,179 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crate::{
+    cache::{self, CachedFile},
+    config::Config,
+    inode::DayFile,
+    web,
+};
+
+// top directory of the archives when no username is configured
+const DEFAULT_ACCOUNT: &str = "default";
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: usize,
+    // already cached with the same content
+    pub identical: usize,
+    // cached with another content that may legitimately differ, like stats or puzzle pages
+    pub kept: usize,
+}
+
+// Writes the files of the cache dir to `path`, a `.tar.zst` archive, under a `<username>/` directory
+// (`default/` without a username)
+pub fn export(config: &Config, path: &Path) -> io::Result<usize> {
+    let account = account(config);
+    let encoder = zstd::Encoder::new(File::create(path)?, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+    let mut exported = 0;
+    for relative in cache::list_files(config.cache_dir())? {
+        // leftover temporary files and anything else aoc-fs didn't write
+        if CachedFile::from_path(&relative).is_none() {
+            log::warn!("not exporting {:?}", relative);
+            continue;
+        }
+
+        // stored decompressed, the archive is compressed as a whole
+        let data = cache::read(&config.cache_dir().join(&relative))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(modified_secs(&config.cache_dir().join(&relative)));
+        builder.append_data(&mut header, Path::new(account).join(&relative), &data[..])?;
+        exported += 1;
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(exported)
+}
+
+// Merges an archive written by `export` for the same account into the cache dir. Nothing is
+// written if the archive is invalid, or if an input or answer differs from the cached one.
+pub fn import(config: &Config, path: &Path) -> io::Result<ImportSummary> {
+    let account = account(config);
+    let mut archive = tar::Archive::new(zstd::Decoder::new(File::open(path)?)?);
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+
+        let relative = match relative_path(account, &entry_path) {
+            Ok(relative) => relative,
+            Err(reason) => return Err(invalid_archive(&entry_path, reason)),
+        };
+
+        if !entry.header().entry_type().is_file() {
+            return Err(invalid_archive(&entry_path, "not a regular file"));
+        }
+
+        let file = match CachedFile::from_path(&relative) {
+            Some(file) => file,
+            None => return Err(invalid_archive(&entry_path, "not a file of the cache")),
+        };
+
+        let modified = UNIX_EPOCH + Duration::from_secs(entry.header().mtime()?);
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if let CachedFile::Day(_, DayFile::Input) = file {
+            if let Err(reason) = web::validate_body(&data) {
+                return Err(invalid_archive(&entry_path, reason));
+            }
+        }
+
+        files.push((relative, file, data, modified));
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut conflicts = Vec::new();
+    let mut new_files = Vec::new();
+    for (relative, file, data, modified) in files {
+        match cache::read(&config.cache_dir().join(&relative)) {
+            Ok(cached) if cached == data => summary.identical += 1,
+            // the same puzzle always has the same input and answers
+            Ok(_) if is_immutable(file) => conflicts.push(relative),
+            Ok(_) => summary.kept += 1,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                new_files.push((relative, file, data, modified))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let conflicts: Vec<String> = conflicts
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let message = format!("Conflicting content for {}", conflicts.join(", "));
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, message));
+    }
+
+    for (relative, file, data, modified) in new_files {
+        let path = config.cache_dir().join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        cache::write(&path, &data, config.compress() && file.is_compressible())?;
+        // the mtime is when the file was downloaded, it decides when it gets refreshed
+        File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified)?;
+        summary.imported += 1;
+    }
+
+    Ok(summary)
+}
+
+// the cache dir of an account is named after it, archives must say which account they are for
+fn account(config: &Config) -> &str {
+    match config.username() {
+        "" => DEFAULT_ACCOUNT,
+        username => username,
+    }
+}
+
+fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |age| age.as_secs())
+}
+
+fn is_immutable(file: CachedFile) -> bool {
+    matches!(
+        file,
+        CachedFile::Day(_, DayFile::Input | DayFile::Answer(_))
+    )
+}
+
+// `account/relative`, with nothing escaping the account directory
+fn relative_path(account: &str, path: &Path) -> Result<PathBuf, &'static str> {
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err("invalid path");
+    }
+
+    match path.strip_prefix(account) {
+        Ok(relative) => Ok(relative.to_path_buf()),
+        Err(_) => Err("belongs to another account"),
+    }
+}
+
+fn invalid_archive(path: &Path, reason: &str) -> io::Error {
+    let message = format!("Invalid archive entry {:?}: {}", path, reason);
+    log::error!("{}", message);
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
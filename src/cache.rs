@@ -0,0 +1,284 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Read, Write},
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    calendar::MAX_DAYS,
+    filesystem::DayAndYear,
+    inode::{DayFile, YearFile, MAX_EXAMPLES},
+};
+
+// compressed files are recognized by their content, the cached text files never start like this
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+// long enough for any zstd frame header
+const ZSTD_MAX_HEADER: usize = 18;
+
+// bad entries are moved there, relative to the cache dir, rather than deleted
+pub const QUARANTINE_DIR: &str = ".quarantine";
+
+// what a file of the cache dir holds, going by its path (see the `cached_*` methods of `Config`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachedFile {
+    Day(DayAndYear, DayFile),
+    InputMetadata(DayAndYear),
+    Year(u32, YearFile),
+    Leaderboard(u64, u32),
+}
+
+impl CachedFile {
+    // `relative` is relative to the cache dir, `None` for anything aoc-fs doesn't write there
+    pub fn from_path(relative: &Path) -> Option<CachedFile> {
+        let components: Vec<&str> = relative
+            .iter()
+            .map(|component| component.to_str())
+            .collect::<Option<_>>()?;
+
+        match components[..] {
+            ["leaderboards", id, name] => {
+                let year = parse_number(name.strip_suffix(".json")?)?;
+                Some(CachedFile::Leaderboard(parse_number(id)?, year))
+            }
+            [year, name] => {
+                let year = parse_number(year)?;
+                if let Some(file) = YearFile::ALL
+                    .into_iter()
+                    .find(|file| file.file_name() == name)
+                {
+                    return Some(CachedFile::Year(year, file));
+                }
+
+                let (day, extension) = name.strip_prefix("day")?.split_once('.')?;
+                let day = DayAndYear::new(
+                    year,
+                    parse_number(day).filter(|day| (1..=MAX_DAYS).contains(day))?,
+                );
+                match extension {
+                    "txt" => Some(CachedFile::Day(day, DayFile::Input)),
+                    "txt.meta" => Some(CachedFile::InputMetadata(day)),
+                    "md" => Some(CachedFile::Day(day, DayFile::Puzzle)),
+                    _ => {
+                        let file = if let Some(part) = extension.strip_prefix("part") {
+                            DayFile::Part(parse_number(part).filter(|part| (1..=2).contains(part))?)
+                        } else if let Some(part) = extension.strip_prefix("answer") {
+                            DayFile::Answer(
+                                parse_number(part).filter(|part| (1..=2).contains(part))?,
+                            )
+                        } else {
+                            let example =
+                                extension.strip_prefix("example")?.strip_suffix(".txt")?;
+                            DayFile::Example(
+                                parse_number(example)
+                                    .filter(|example| (1..=MAX_EXAMPLES).contains(example))?,
+                            )
+                        };
+
+                        Some(CachedFile::Day(day, file))
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn year(self) -> u32 {
+        match self {
+            CachedFile::Day(day, _) | CachedFile::InputMetadata(day) => day.year,
+            CachedFile::Year(year, _) | CachedFile::Leaderboard(_, year) => year,
+        }
+    }
+
+    // files that may be stored compressed, the others are small or rewritten often
+    pub fn is_compressible(self) -> bool {
+        match self {
+            CachedFile::Day(_, DayFile::Part(_)) => false,
+            CachedFile::Day(..) | CachedFile::Leaderboard(..) => true,
+            CachedFile::InputMetadata(_) | CachedFile::Year(..) => false,
+        }
+    }
+
+    pub fn day(self) -> Option<DayAndYear> {
+        match self {
+            CachedFile::Day(day, _) | CachedFile::InputMetadata(day) => Some(day),
+            CachedFile::Year(..) | CachedFile::Leaderboard(..) => None,
+        }
+    }
+}
+
+// Files of the cache dir, relative to it and sorted, except the quarantined ones
+pub fn list_files(cache_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    list_dir(cache_dir, Path::new(""), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn list_dir(cache_dir: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(cache_dir.join(dir))? {
+        let entry = entry?;
+        let relative = dir.join(entry.file_name());
+        if relative == Path::new(QUARANTINE_DIR) {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            list_dir(cache_dir, &relative, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+
+    Ok(())
+}
+
+// numbers are written without leading zeros, anything else is not a name aoc-fs uses
+fn parse_number<T: std::str::FromStr + ToString>(number: &str) -> Option<T> {
+    number
+        .parse::<T>()
+        .ok()
+        .filter(|parsed| parsed.to_string() == number)
+}
+
+// what is known about a downloaded file, stored next to it as `<name>.meta`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    pub downloaded_at: DateTime<Utc>,
+    // username of the account the session belongs to
+    pub account: String,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl Metadata {
+    pub fn new(account: &str, status: u16, headers: BTreeMap<String, String>, data: &[u8]) -> Self {
+        Self {
+            downloaded_at: Utc::now(),
+            account: account.to_string(),
+            status,
+            headers,
+            size: data.len() as u64,
+            sha256: sha256(data),
+        }
+    }
+
+    // metadata of the cached file at `path`, if it was recorded
+    pub fn load(path: &Path) -> Option<Metadata> {
+        let meta_path = metadata_path(path);
+        let contents = fs::read_to_string(&meta_path).ok()?;
+        match toml::from_str(&contents) {
+            Ok(metadata) => Some(metadata),
+            Err(err) => {
+                log::warn!("Invalid metadata in {:?}: {}", meta_path, err);
+                None
+            }
+        }
+    }
+
+    // writes the metadata of the cached file at `path`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = toml::to_string(self).expect("Metadata is serializable");
+        write_atomically(&metadata_path(path), contents.as_bytes())
+    }
+}
+
+pub fn sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub fn metadata_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".meta");
+    PathBuf::from(path)
+}
+
+// Writes `contents` next to `path` then renames it over, so `path` is either missing, the previous
+// version or the complete new one
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = temp_path(path);
+    let written = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+
+    match written.and_then(|()| fs::rename(&temp_path, path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            log::error!("Failed to write {:?}: {}", path, err);
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+// Like `write_atomically`, zstd-compressed if `compress`
+pub fn write(path: &Path, contents: &[u8], compress: bool) -> io::Result<()> {
+    if !compress {
+        return write_atomically(path, contents);
+    }
+
+    // unlike the streaming encoder, this records the uncompressed size in the frame header
+    let compressed = zstd::bulk::compress(contents, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    write_atomically(path, &compressed)
+}
+
+// Contents of a cached file, decompressed if needed
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    read_from(&mut File::open(path)?)
+}
+
+pub fn read_from(file: &mut File) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    if data.starts_with(&ZSTD_MAGIC) {
+        zstd::decode_all(&data[..])
+    } else {
+        Ok(data)
+    }
+}
+
+pub fn is_compressed(file: &File) -> bool {
+    let mut magic = [0; ZSTD_MAGIC.len()];
+    file.read_exact_at(&mut magic, 0).is_ok() && magic == ZSTD_MAGIC
+}
+
+// Uncompressed size of a cached file
+pub fn size(path: &Path) -> io::Result<u64> {
+    let file = File::open(path)?;
+    if !is_compressed(&file) {
+        return Ok(file.metadata()?.len());
+    }
+
+    let mut header = [0; ZSTD_MAX_HEADER];
+    let read = file.read_at(&mut header, 0)?;
+    match zstd::zstd_safe::get_frame_content_size(&header[..read]) {
+        Ok(Some(size)) => Ok(size),
+        // not written by `write`
+        _ => Ok(read_from(&mut File::open(path)?)?.len() as u64),
+    }
+}
+
+// `dir/.name.<pid>.<n>.tmp`, hidden and in the same directory so the rename stays on the same
+// filesystem, unique so concurrent writes of the same file never share it
+fn temp_path(path: &Path) -> PathBuf {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
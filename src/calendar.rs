@@ -0,0 +1,366 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+};
+
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
+
+use crate::{config::Config, filesystem::DayAndYear, html, web};
+
+// inode numbers leave room for 25 days per event
+pub const MAX_DAYS: u8 = 25;
+// the first event, the inodes of earlier years are used by the files of the root
+pub const FIRST_YEAR: u32 = 2015;
+// inode numbers leave room for 4-digit years
+pub const LAST_YEAR: u32 = 9999;
+
+// puzzles unlock at midnight UTC-5
+pub fn aoc_offset() -> FixedOffset {
+    FixedOffset::west_opt(3600 * 5).expect("FixedOffset::west_opt(3600 * 5) returned None")
+}
+
+// which events exist, how many puzzles they have and when those unlock
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    // number of puzzles of the events from the given year on, the first key is the first event
+    days: BTreeMap<u32, u8>,
+    // years listed on `/events`, if it was fetched
+    events: Option<BTreeSet<u32>>,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            days: BTreeMap::from([(FIRST_YEAR, 25), (2025, 12)]),
+            events: None,
+        }
+    }
+}
+
+impl Calendar {
+    // `overrides` maps years to the number of puzzles of the events from that year on
+    pub fn with_overrides(overrides: BTreeMap<u32, u8>) -> Calendar {
+        let mut calendar = Calendar::default();
+        calendar.days.extend(overrides);
+        calendar
+    }
+
+    pub fn set_events(&mut self, events: BTreeSet<u32>) {
+        self.events = Some(events);
+    }
+
+    pub fn first_year(&self) -> u32 {
+        *self.days.keys().next().expect("calendar without events")
+    }
+
+    // number of puzzles of the event of `year`, `None` if there is no such event
+    // (years after the last one listed on `/events` are assumed to have one)
+    pub fn days(&self, year: u32) -> Option<u8> {
+        let (_, &days) = self.days.range(..=year).next_back()?;
+        match &self.events {
+            Some(events)
+                if !events.contains(&year) && events.last().is_some_and(|&last| year <= last) =>
+            {
+                None
+            }
+            _ => Some(days),
+        }
+    }
+
+    // years with an event, up to `last_year`
+    pub fn years(&self, last_year: u32) -> impl Iterator<Item = u32> + '_ {
+        (self.first_year()..=last_year).filter(|&year| self.days(year).is_some())
+    }
+
+    // number of puzzles of `year` released once `latest` is, `None` if its event didn't start
+    pub fn released_days(&self, year: u32, latest: DayAndYear) -> Option<u8> {
+        let days = self.days(year)?;
+        if year > latest.year {
+            None
+        } else if year == latest.year {
+            Some(latest.day)
+        } else {
+            Some(days)
+        }
+    }
+
+    pub fn unlock_time(&self, day: DayAndYear) -> DateTime<FixedOffset> {
+        aoc_offset()
+            .with_ymd_and_hms(day.year as i32, 12, day.day as u32, 0, 0, 0)
+            .single()
+            .expect("puzzles unlock at midnight UTC-5")
+    }
+
+    // day 0 of the year before the first event if nothing is released at `now`
+    pub fn last_unlocked_puzzle(&self, now: DateTime<Utc>) -> DayAndYear {
+        let year = now.with_timezone(&aoc_offset()).year() as u32;
+        if let Some(days) = self.days(year) {
+            let released = (1..=days)
+                .take_while(|&day| self.unlock_time(DayAndYear::new(year, day)) <= now)
+                .count();
+            if released > 0 {
+                return DayAndYear::new(year, released as u8);
+            }
+        }
+
+        (self.first_year()..year)
+            .rev()
+            .find_map(|year| Some(DayAndYear::new(year, self.days(year)?)))
+            .unwrap_or(DayAndYear::new(self.first_year() - 1, 0))
+    }
+
+    // next puzzle to be released, with its release time
+    pub fn next_unlock(&self, now: DateTime<Utc>) -> (DayAndYear, DateTime<FixedOffset>) {
+        let year = now.with_timezone(&aoc_offset()).year() as u32;
+        (year..)
+            .flat_map(|year| {
+                (1..=self.days(year).unwrap_or(0)).map(move |day| DayAndYear::new(year, day))
+            })
+            .map(|day| (day, self.unlock_time(day)))
+            .find(|(_, unlock)| *unlock > now)
+            .expect("years after the last known event always have one")
+    }
+}
+
+// The calendar of `config`, with the list of events refreshed first if enabled
+pub fn from_config(config: &Config) -> Calendar {
+    let mut calendar = config.calendar().clone();
+    refresh_events(&mut calendar, config);
+    calendar
+}
+
+// Replaces the events of `calendar` with the ones listed on `/events` when `calendar.refresh_events`
+// is set, returns the result of the download if there was one
+pub fn refresh_events(calendar: &mut Calendar, config: &Config) -> Option<io::Result<()>> {
+    if !config.refresh_events() {
+        return None;
+    }
+
+    let refreshed =
+        download_events(config.session_token()).map(|events| calendar.set_events(events));
+    if let Err(err) = &refreshed {
+        log::warn!("Could not refresh the list of events: {}", err);
+    }
+
+    Some(refreshed)
+}
+
+// Downloads `/events` and returns the years it lists
+pub fn download_events(session: &str) -> io::Result<BTreeSet<u32>> {
+    let page = web::download_events_page(session)?;
+    let events = parse_events(&page);
+    if events.is_empty() {
+        let message = "No events found on the events page".to_string();
+        log::error!("{}", message);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+
+    Ok(events)
+}
+
+// Each event is a `div.eventlist-event` starting with a link to `/YYYY`
+pub fn parse_events(page: &str) -> BTreeSet<u32> {
+    let document = html::parse(page);
+    document
+        .find_all(|el| el.name == "div" && el.has_class("eventlist-event"))
+        .into_iter()
+        .filter_map(|event| {
+            let link = event.find_all(|el| el.name == "a").into_iter().next()?;
+            link.attr("href")?.trim_matches('/').parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use chrono::{DateTime, Utc};
+
+    use super::Calendar;
+    use crate::{clock::Clock, filesystem::DayAndYear};
+
+    fn clock(date: &str) -> Clock {
+        let date = DateTime::parse_from_rfc3339(date).expect("valid RFC 3339 date");
+        Clock::Fixed(date.with_timezone(&Utc))
+    }
+
+    fn last_unlocked(date: &str) -> DayAndYear {
+        Calendar::default().last_unlocked_puzzle(clock(date).now())
+    }
+
+    // the next puzzle, and its unlock as RFC 3339
+    fn next_unlock(date: &str) -> (DayAndYear, String) {
+        let (day, unlock) = Calendar::default().next_unlock(clock(date).now());
+        (day, unlock.to_rfc3339())
+    }
+
+    #[test]
+    fn first_puzzle_unlocks_at_midnight_utc_minus_5() {
+        assert_eq!(
+            last_unlocked("2024-11-30T23:59:59-05:00"),
+            DayAndYear::new(2023, 25)
+        );
+        assert_eq!(
+            next_unlock("2024-11-30T23:59:59-05:00"),
+            (
+                DayAndYear::new(2024, 1),
+                "2024-12-01T00:00:00-05:00".to_string()
+            )
+        );
+
+        assert_eq!(
+            last_unlocked("2024-12-01T00:00:00-05:00"),
+            DayAndYear::new(2024, 1)
+        );
+        assert_eq!(
+            next_unlock("2024-12-01T00:00:00-05:00"),
+            (
+                DayAndYear::new(2024, 2),
+                "2024-12-02T00:00:00-05:00".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn day_ends_at_midnight_utc_minus_5() {
+        assert_eq!(
+            last_unlocked("2024-12-01T23:59:59-05:00"),
+            DayAndYear::new(2024, 1)
+        );
+        // already December 2nd in UTC
+        assert_eq!(
+            last_unlocked("2024-12-02T04:59:59Z"),
+            DayAndYear::new(2024, 1)
+        );
+        assert_eq!(
+            last_unlocked("2024-12-02T05:00:00Z"),
+            DayAndYear::new(2024, 2)
+        );
+    }
+
+    #[test]
+    fn events_before_2025_have_25_puzzles() {
+        assert_eq!(
+            last_unlocked("2024-12-12T12:00:00-05:00"),
+            DayAndYear::new(2024, 12)
+        );
+        assert_eq!(
+            next_unlock("2024-12-12T12:00:00-05:00"),
+            (
+                DayAndYear::new(2024, 13),
+                "2024-12-13T00:00:00-05:00".to_string()
+            )
+        );
+
+        assert_eq!(
+            last_unlocked("2024-12-25T12:00:00-05:00"),
+            DayAndYear::new(2024, 25)
+        );
+        assert_eq!(
+            next_unlock("2024-12-25T12:00:00-05:00"),
+            (
+                DayAndYear::new(2025, 1),
+                "2025-12-01T00:00:00-05:00".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn events_from_2025_have_12_puzzles() {
+        let calendar = Calendar::default();
+        assert_eq!(calendar.days(2024), Some(25));
+        assert_eq!(calendar.days(2025), Some(12));
+        assert_eq!(calendar.days(2030), Some(12));
+
+        assert_eq!(
+            last_unlocked("2025-12-12T12:00:00-05:00"),
+            DayAndYear::new(2025, 12)
+        );
+        assert_eq!(
+            last_unlocked("2025-12-25T12:00:00-05:00"),
+            DayAndYear::new(2025, 12)
+        );
+        assert_eq!(
+            next_unlock("2025-12-12T12:00:00-05:00"),
+            (
+                DayAndYear::new(2026, 1),
+                "2026-12-01T00:00:00-05:00".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn january_belongs_to_the_previous_event() {
+        assert_eq!(
+            last_unlocked("2025-01-15T12:00:00-05:00"),
+            DayAndYear::new(2024, 25)
+        );
+        assert_eq!(
+            next_unlock("2025-01-15T12:00:00-05:00"),
+            (
+                DayAndYear::new(2025, 1),
+                "2025-12-01T00:00:00-05:00".to_string()
+            )
+        );
+        assert_eq!(
+            last_unlocked("2026-01-15T12:00:00-05:00"),
+            DayAndYear::new(2025, 12)
+        );
+    }
+
+    #[test]
+    fn events_hide_the_years_they_dont_list() {
+        let mut calendar = Calendar::default();
+        calendar.set_events(BTreeSet::from([2015, 2016, 2018, 2024]));
+        assert_eq!(calendar.days(2014), None);
+        assert_eq!(calendar.days(2015), Some(25));
+        assert_eq!(calendar.days(2017), None);
+        assert_eq!(calendar.days(2024), Some(25));
+        assert_eq!(
+            calendar.years(2024).collect::<Vec<_>>(),
+            [2015, 2016, 2018, 2024]
+        );
+    }
+
+    #[test]
+    fn years_after_the_last_event_are_assumed_to_exist() {
+        let mut calendar = Calendar::with_overrides(BTreeMap::from([(2027, 20)]));
+        calendar.set_events(BTreeSet::from([2023, 2024]));
+        assert_eq!(calendar.days(2022), None);
+        assert_eq!(calendar.days(2025), Some(12));
+        assert_eq!(calendar.days(2026), Some(12));
+        assert_eq!(calendar.days(2027), Some(20));
+        assert_eq!(
+            calendar.years(2027).collect::<Vec<_>>(),
+            [2023, 2024, 2025, 2026, 2027]
+        );
+    }
+
+    #[test]
+    fn unlisted_events_are_skipped() {
+        let mut calendar = Calendar::default();
+        calendar.set_events(BTreeSet::from([2015, 2017]));
+        let now = clock("2017-01-15T12:00:00-05:00").now();
+        assert_eq!(
+            calendar.last_unlocked_puzzle(now),
+            DayAndYear::new(2015, 25)
+        );
+        assert_eq!(calendar.next_unlock(now).0, DayAndYear::new(2017, 1));
+    }
+
+    #[test]
+    fn nothing_released_before_the_first_event() {
+        assert_eq!(
+            last_unlocked("2015-11-30T12:00:00-05:00"),
+            DayAndYear::new(2014, 0)
+        );
+        assert_eq!(
+            next_unlock("2015-11-30T12:00:00-05:00"),
+            (
+                DayAndYear::new(2015, 1),
+                "2015-12-01T00:00:00-05:00".to_string()
+            )
+        );
+    }
+}
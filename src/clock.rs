@@ -0,0 +1,112 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+
+use crate::calendar::aoc_offset;
+
+// source of "now" for everything depending on the puzzles release schedule
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    // the system clock, shifted
+    Offset(TimeDelta),
+    // always the same instant
+    Fixed(DateTime<Utc>),
+}
+
+impl Clock {
+    // pretends the current time is `date`, the clock keeps running unless `frozen`
+    pub fn pretend(date: DateTime<Utc>, frozen: bool) -> Clock {
+        if frozen {
+            Clock::Fixed(date)
+        } else {
+            Clock::Offset(date - Utc::now())
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Offset(offset) => Utc::now() + *offset,
+            Clock::Fixed(date) => *date,
+        }
+    }
+}
+
+// `2024-12-03` (midnight), `2024-12-03T23:59:50` (both in AoC time, UTC-5) or a RFC 3339 date
+pub fn parse_date(date: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    let naive = match NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S") {
+        Ok(naive) => naive,
+        Err(_) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => date.and_time(Default::default()),
+            Err(e) => return Err(format!("invalid date {:?}: {}", date, e)),
+        },
+    };
+
+    match aoc_offset().from_local_datetime(&naive).single() {
+        Some(date) => Ok(date.with_timezone(&Utc)),
+        None => Err(format!("invalid date {:?}", date)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use super::{parse_date, Clock};
+
+    fn utc(date: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(date)
+            .expect("valid RFC 3339 date")
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn date_is_midnight_utc_minus_5() {
+        assert_eq!(parse_date("2024-12-03"), Ok(utc("2024-12-03T05:00:00Z")));
+    }
+
+    #[test]
+    fn date_and_time_are_utc_minus_5() {
+        assert_eq!(
+            parse_date("2024-12-03T23:59:50"),
+            Ok(utc("2024-12-04T04:59:50Z"))
+        );
+    }
+
+    #[test]
+    fn rfc_3339_keeps_its_offset() {
+        assert_eq!(
+            parse_date("2024-12-03T23:59:50+01:00"),
+            Ok(utc("2024-12-03T22:59:50Z"))
+        );
+        assert_eq!(
+            parse_date("2024-12-04T05:00:00Z"),
+            Ok(utc("2024-12-04T05:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("2024-12-03 23:59:50").is_err());
+        assert!(parse_date("tomorrow").is_err());
+    }
+
+    #[test]
+    fn fixed_clock_does_not_move() {
+        let date = utc("2024-12-03T05:00:00Z");
+        assert_eq!(Clock::pretend(date, true).now(), date);
+        assert_eq!(Clock::Fixed(date).now(), date);
+    }
+
+    #[test]
+    fn pretended_clock_starts_at_the_date() {
+        let date = utc("2024-12-03T05:00:00Z");
+        let elapsed = Clock::pretend(date, false).now() - date;
+        assert!(elapsed >= TimeDelta::zero() && elapsed < TimeDelta::seconds(60));
+    }
+}
@@ -1,33 +1,83 @@
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
 };
 
 use serde::Deserialize;
 
-use crate::filesystem::DayAndYear;
+use crate::{
+    calendar::{Calendar, FIRST_YEAR, LAST_YEAR, MAX_DAYS},
+    filesystem::DayAndYear,
+};
 
 #[derive(Debug)]
 pub struct Config {
+    username: String,
     session_token: String,
     cache_dir: PathBuf,
+    compress: bool,
+    layout: Layout,
+    wait_for_unlock: bool,
+    leaderboards: Vec<u64>,
+    calendar: Calendar,
+    refresh_events: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    // `YYYY/dayNN.txt`, `YYYY/dayNN.md`, ...
+    #[default]
+    Flat,
+    // `YYYY/dayNN/input.txt`, `YYYY/dayNN/puzzle.md`, ...
+    PerDay,
 }
 
 #[derive(Debug, Deserialize)]
 struct TomlConf {
     aoc: AocConf,
     cache: CacheConf,
+    #[serde(default)]
+    fs: FsConf,
+    #[serde(default)]
+    calendar: CalendarConf,
 }
 
 #[derive(Debug, Deserialize)]
 struct AocConf {
     username: String,
     session: String,
+    // ids of the private leaderboards to expose
+    #[serde(default)]
+    leaderboards: Vec<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct CacheConf {
     dir: PathBuf,
+    // store inputs, puzzle pages and leaderboards zstd-compressed
+    #[serde(default)]
+    compress: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FsConf {
+    #[serde(default)]
+    layout: Layout,
+    // show the next puzzle during the hour before it unlocks, opening it waits for the unlock
+    #[serde(default)]
+    wait_for_unlock: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CalendarConf {
+    // number of puzzles of the events from the given year on, e.g. `{ 2025 = 12 }`
+    #[serde(default)]
+    days: BTreeMap<String, u8>,
+    // fetch the list of events from `/events` when mounting
+    #[serde(default)]
+    refresh_events: bool,
 }
 
 impl Config {
@@ -47,9 +97,37 @@ impl Config {
             }
         }
 
+        let mut days = BTreeMap::new();
+        for (year, count) in config.calendar.days {
+            let year = match year.parse::<u32>() {
+                Ok(year) => year,
+                Err(_) => return Err(format!("invalid year in calendar.days: {:?}", year).into()),
+            };
+
+            if !(FIRST_YEAR..=LAST_YEAR).contains(&year) {
+                return Err(format!(
+                    "calendar.days: {year} is not between {FIRST_YEAR} and {LAST_YEAR}"
+                )
+                .into());
+            }
+
+            if !(1..=MAX_DAYS).contains(&count) {
+                return Err(format!("calendar.days: {year} must have 1 to {MAX_DAYS} days").into());
+            }
+
+            days.insert(year, count);
+        }
+
         Ok(Config {
+            username: config.aoc.username,
             session_token: config.aoc.session,
             cache_dir,
+            compress: config.cache.compress,
+            layout: config.fs.layout,
+            wait_for_unlock: config.fs.wait_for_unlock,
+            leaderboards: config.aoc.leaderboards,
+            calendar: Calendar::with_overrides(days),
+            refresh_events: config.calendar.refresh_events,
         })
     }
 
@@ -58,6 +136,11 @@ impl Config {
         &self.cache_dir
     }
 
+    #[inline]
+    pub fn compress(&self) -> bool {
+        self.compress
+    }
+
     #[inline]
     pub fn cached_day_input(&self, day: DayAndYear) -> PathBuf {
         let mut path = self.cache_dir().to_path_buf();
@@ -67,8 +150,121 @@ impl Config {
         path
     }
 
+    #[inline]
+    pub fn cached_day_puzzle(&self, day: DayAndYear) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", day.year));
+        path.push(format!("day{}.md", day.day));
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_day_example(&self, day: DayAndYear, example: u8) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", day.year));
+        path.push(format!("day{}.example{}.txt", day.day, example));
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_day_verdict(&self, day: DayAndYear, part: u8) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", day.year));
+        path.push(format!("day{}.part{}", day.day, part));
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_day_answer(&self, day: DayAndYear, part: u8) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", day.year));
+        path.push(format!("day{}.answer{}", day.day, part));
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_year_stats(&self, year: u32) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", year));
+        path.push("stats.txt");
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_year_stats_json(&self, year: u32) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", year));
+        path.push("stats.json");
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_year_stars(&self, year: u32) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", year));
+        path.push("stars");
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_year_stars_json(&self, year: u32) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push(format!("{}", year));
+        path.push("stars.json");
+
+        path
+    }
+
+    #[inline]
+    pub fn cached_leaderboard(&self, id: u64, year: u32) -> PathBuf {
+        let mut path = self.cache_dir().to_path_buf();
+        path.push("leaderboards");
+        path.push(format!("{}", id));
+        path.push(format!("{}.json", year));
+
+        path
+    }
+
+    // the account the session belongs to, as configured
+    #[inline]
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
     #[inline]
     pub fn session_token(&self) -> &str {
         &self.session_token
     }
+
+    #[inline]
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    #[inline]
+    pub fn wait_for_unlock(&self) -> bool {
+        self.wait_for_unlock
+    }
+
+    #[inline]
+    pub fn calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    #[inline]
+    pub fn refresh_events(&self) -> bool {
+        self.refresh_events
+    }
+
+    #[inline]
+    pub fn leaderboards(&self) -> &[u64] {
+        &self.leaderboards
+    }
 }
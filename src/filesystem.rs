@@ -1,27 +1,46 @@
 use core::str;
 use std::{
+    collections::HashMap,
+    ffi::OsStr,
     fs::{self, File},
     io,
-    mem::ManuallyDrop,
-    os::{
-        fd::{FromRawFd, IntoRawFd},
-        unix::{
-            ffi::OsStrExt,
-            fs::{FileExt, MetadataExt},
-        },
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex, MutexGuard, OnceLock,
     },
-    time::{Duration, UNIX_EPOCH},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use fuser::FileAttr;
 
-use crate::{config::Config, web};
+use crate::{
+    cache::{self, Metadata},
+    calendar::{self, Calendar, MAX_DAYS},
+    clock::Clock,
+    config::{Config, Layout},
+    inode::{DayFile, Node, YearFile, MAX_EXAMPLES},
+    leaderboard, puzzle, stars, stats,
+    status::Status,
+    web,
+};
 
-// AoC started in 2015, so year 2000 day 0 can be used as a marker for the `latest` symlink at fs root
-const LATEST_ROOT_INO: u64 = DayAndYear::new(2000, 0).to_ino();
-const AOC_FIRST_YEAR: u32 = 2015;
-const AOC_FIRST_YEAR_WITH_12_DAYS: u32 = 2025;
+const MAX_ANSWER_LEN: usize = 4096;
+// margin after the unlock instant, in case the local clock is a bit early
+const UNLOCK_GRACE: Duration = Duration::from_secs(1);
+// longest time the kernel may cache attributes and entries, even for files that never change
+const MAX_TTL: Duration = Duration::from_secs(24 * 3600);
+// with `wait_for_unlock`, the next puzzle is listed this long before it unlocks
+const PENDING_WINDOW: Duration = Duration::from_secs(3600);
+const ANSWER_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const STATS_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const STARS_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// AoC asks not to request private leaderboards more than once every 15 minutes
+const LEADERBOARD_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DayAndYear {
@@ -30,37 +49,6 @@ pub struct DayAndYear {
 }
 
 impl DayAndYear {
-    pub fn last_day_of_year(year: u32) -> DayAndYear {
-        DayAndYear::new(
-            year,
-            if year < AOC_FIRST_YEAR_WITH_12_DAYS {
-                25
-            } else {
-                12
-            },
-        )
-    }
-
-    pub fn last_unlocked_puzzle() -> DayAndYear {
-        let now = Utc::now();
-        let current_time: DateTime<FixedOffset> = now.with_timezone(
-            &FixedOffset::west_opt(3600 * 5)
-                .expect("FixedOffset::east_opt(3600 * 5) returned None"),
-        );
-
-        if current_time.month() == 12 {
-            let mut day = current_time.day() as u8;
-            let last_day = DayAndYear::last_day_of_year(current_time.year() as u32);
-            if day > last_day.day {
-                day = last_day.day;
-            }
-
-            DayAndYear::new(current_time.year() as u32, day)
-        } else {
-            DayAndYear::last_day_of_year((current_time.year() - 1) as u32)
-        }
-    }
-
     pub const fn new(year: u32, day: u8) -> DayAndYear {
         DayAndYear { year, day }
     }
@@ -75,21 +63,6 @@ impl DayAndYear {
     pub const fn to_ino(self) -> u64 {
         (self.year * 100) as u64 + self.day as u64
     }
-
-    pub const fn file_type(self) -> Result<fuser::FileType, libc::c_int> {
-        use fuser::FileType;
-
-        if self.year < AOC_FIRST_YEAR_WITH_12_DAYS && matches!(self.day, 1..=25) {
-            return Ok(FileType::RegularFile);
-        }
-
-        match self.day {
-            0 => Ok(FileType::Directory),
-            1..=12 => Ok(FileType::RegularFile),
-            26 => Ok(FileType::Symlink),
-            _ => Err(libc::ENOENT),
-        }
-    }
 }
 
 impl PartialOrd for DayAndYear {
@@ -107,19 +80,332 @@ impl Ord for DayAndYear {
 }
 
 #[derive(Debug)]
+enum Handle {
+    Data(Arc<[u8]>),
+    // an answer being written to `dayNN.partN`, submitted on flush
+    Answer {
+        day: DayAndYear,
+        part: u8,
+        answer: Vec<u8>,
+        submitted: bool,
+    },
+}
+
+// held while a cached file is checked and downloaded, with the time and errno of its last failed
+// download
+type DownloadLock = Arc<Mutex<Option<(Instant, libc::c_int)>>>;
+
+// cloned into the threads waiting for a puzzle to unlock
+#[derive(Debug, Clone)]
 pub struct AoCFilesystem {
     uid: u32,
     gid: u32,
-    config: Config,
+    config: Arc<Config>,
+    handles: Arc<Mutex<HashMap<u64, Handle>>>,
+    // contents of the cached files that never change (inputs and accepted answers) by inode,
+    // read once and shared by all their handles
+    contents: Arc<Mutex<HashMap<u64, Arc<[u8]>>>>,
+    // concurrent opens download a file once, the others find it cached
+    downloads: Arc<Mutex<HashMap<PathBuf, DownloadLock>>>,
+    next_fh: Arc<AtomicU64>,
+    status: Arc<Mutex<Status>>,
+    clock: Clock,
+    calendar: Arc<Calendar>,
+    // to the thread invalidating the kernel caches, once mounted
+    invalidations: Arc<OnceLock<mpsc::Sender<u64>>>,
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), libc::c_int> {
+    let parent = path.parent().expect("No parent for cache path???");
+    if parent.exists() {
+        return Ok(());
+    }
+
+    match fs::create_dir_all(parent) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            log::error!("Could not create cache directory {:?}: {}", parent, e);
+            Err(e
+                .raw_os_error()
+                .expect("fs::create_dir_all() => Err(e) => e.raw_os_error()"))
+        }
+    }
+}
+
+// uncompressed size of a cached file
+fn cached_file_size(path: &Path) -> Option<u64> {
+    match cache::size(path) {
+        Ok(size) => Some(size),
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("cache_dir metadata error: {}", err);
+            }
+
+            None
+        }
+    }
+}
+
+// files are served from memory, decompressed if needed
+fn load(mut fd: File) -> Result<Arc<[u8]>, libc::c_int> {
+    match cache::read_from(&mut fd) {
+        Ok(data) => Ok(data.into()),
+        Err(err) => {
+            log::error!("error reading a cached file: {}", err);
+            Err(errno(&err))
+        }
+    }
+}
+
+// what a read of `size` bytes at `offset` returns: short at the end, empty past it,
+// `None` for a negative offset
+fn slice_at(data: &[u8], offset: i64, size: u32) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?.min(data.len());
+    let end = start.saturating_add(size as usize).min(data.len());
+    Some(&data[start..end])
+}
+
+// errno reported for a failed download or request
+fn errno(err: &io::Error) -> libc::c_int {
+    match err.raw_os_error() {
+        Some(errno) => errno,
+        None if err.kind() == io::ErrorKind::NetworkDown => libc::ENETDOWN,
+        None => libc::EIO,
+    }
+}
+
+// ctime/crtime at `unlock`, mtime/atime at the download of `cached` (or at the unlock as well)
+fn set_times(attr: &mut FileAttr, unlock: DateTime<FixedOffset>, cached: Option<&Path>) {
+    let unlock = SystemTime::from(unlock);
+    let downloaded = cached
+        .and_then(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .unwrap_or(unlock);
+
+    attr.ctime = unlock;
+    attr.crtime = unlock;
+    attr.mtime = downloaded;
+    attr.atime = downloaded;
+}
+
+fn is_older_than(file: &File, max_age: Duration) -> bool {
+    file.metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_none_or(|age| age >= max_age)
+}
+
+// nothing downloaded is empty, such files were left by failed downloads of older versions
+fn is_empty(file: &File) -> bool {
+    file.metadata().is_ok_and(|metadata| metadata.len() == 0)
+}
+
+// `size` 0 asks for the length of the value, which must otherwise fit in `size` bytes
+fn reply_xattr(reply: fuser::ReplyXattr, value: &[u8], size: u32) {
+    if size == 0 {
+        reply.size(value.len() as u32);
+    } else if value.len() > size as usize {
+        reply.error(libc::ERANGE);
+    } else {
+        reply.data(value);
+    }
+}
+
+fn parse_day(name: &str) -> Option<u8> {
+    let name = name.trim_start_matches("day").trim_start_matches('0');
+    name.parse::<u8>()
+        .ok()
+        .filter(|day| (1..=MAX_DAYS).contains(day))
+}
+
+fn parse_example(example: &str) -> Result<u8, libc::c_int> {
+    match example.parse::<u8>() {
+        Ok(example) if (1..=MAX_EXAMPLES).contains(&example) => Ok(example),
+        _ => Err(libc::ENOENT),
+    }
+}
+
+fn parse_part(part: &str) -> Result<u8, libc::c_int> {
+    match part {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(libc::ENOENT),
+    }
+}
+
+fn day_dir_name(day: u8) -> String {
+    format!("day{day:02}")
+}
+
+fn until_next_unlock(calendar: &Calendar, clock: Clock) -> Duration {
+    let now = clock.now();
+    let (_, unlock) = calendar.next_unlock(now);
+    unlock
+        .signed_duration_since(now)
+        .to_std()
+        .unwrap_or(Duration::ZERO)
+        .min(MAX_TTL)
+}
+
+// the listings change when the next puzzle becomes pending, then when it unlocks
+fn until_listing_change(calendar: &Calendar, clock: Clock, wait_for_unlock: bool) -> Duration {
+    let until_unlock = until_next_unlock(calendar, clock);
+    if wait_for_unlock && until_unlock > PENDING_WINDOW {
+        until_unlock - PENDING_WINDOW
+    } else {
+        until_unlock
+    }
+}
+
+// the next puzzle, when its files are shown before it unlocks
+fn pending_day(calendar: &Calendar, clock: Clock, wait_for_unlock: bool) -> Option<DayAndYear> {
+    if wait_for_unlock && until_next_unlock(calendar, clock) <= PENDING_WINDOW {
+        Some(calendar.next_unlock(clock.now()).0)
+    } else {
+        None
+    }
+}
+
+// the year in the root and the files (or directory) of `day` in the year directory
+fn day_entries(layout: Layout, day: DayAndYear) -> Vec<(u64, String)> {
+    let year = Node::Year(day.year).ino();
+    let mut entries = vec![(Node::Root.ino(), day.year.to_string())];
+    match layout {
+        Layout::Flat => {
+            for file in [
+                DayFile::Input,
+                DayFile::Puzzle,
+                DayFile::Part(1),
+                DayFile::Part(2),
+            ] {
+                entries.push((year, file.file_name(day.day, layout)));
+            }
+        }
+        Layout::PerDay => entries.push((year, day_dir_name(day.day))),
+    }
+
+    entries
+}
+
+// entries that were never looked up aren't in the kernel cache, and fail with ENOENT
+fn invalidate_entries(notifier: &fuser::Notifier, entries: Vec<(u64, String)>) {
+    for (parent, name) in entries {
+        let _ = notifier.inval_entry(parent, OsStr::new(&name));
+    }
+}
+
+// the pending puzzle appears in the listings, and replaces negative entries
+fn invalidate_pending(notifier: &fuser::Notifier, layout: Layout, day: DayAndYear) {
+    log::info!("{}/day{:02} pending", day.year, day.day);
+
+    invalidate_entries(notifier, day_entries(layout, day));
+    for node in [Node::Root, Node::Year(day.year)] {
+        let _ = notifier.inval_inode(node.ino(), 0, 0);
+    }
+}
+
+// the new puzzle changes the listings and the `latest`/`next` links, and replaces negative entries
+fn invalidate_unlocked(notifier: &fuser::Notifier, layout: Layout, day: DayAndYear) {
+    log::info!("{}/day{:02} unlocked", day.year, day.day);
+
+    let root = Node::Root.ino();
+    let mut entries = day_entries(layout, day);
+    entries.extend([
+        (root, "latest".to_string()),
+        (root, "next".to_string()),
+        (Node::Year(day.year).ino(), "latest".to_string()),
+    ]);
+    invalidate_entries(notifier, entries);
+
+    for node in [
+        Node::Root,
+        Node::LatestYear,
+        Node::Next,
+        Node::Year(day.year),
+        Node::LatestDay(day.year),
+    ] {
+        let _ = notifier.inval_inode(node.ino(), 0, 0);
+    }
 }
 
 impl AoCFilesystem {
-    pub fn new(config: Config) -> Self {
-        // dbg!(DayAndYear::last_unlocked_puzzle());
-        Self {
+    pub fn new(config: Config, clock: Clock) -> Self {
+        let mut calendar = config.calendar().clone();
+        let refreshed = calendar::refresh_events(&mut calendar, &config);
+        let fs = Self {
             uid: unsafe { libc::getuid() },
             gid: unsafe { libc::getgid() },
-            config,
+            calendar: Arc::new(calendar),
+            config: Arc::new(config),
+            clock,
+            handles: Arc::new(Mutex::new(HashMap::new())),
+            contents: Arc::new(Mutex::new(HashMap::new())),
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+            next_fh: Arc::new(AtomicU64::new(1)),
+            status: Arc::new(Mutex::new(Status::default())),
+            invalidations: Arc::new(OnceLock::new()),
+        };
+
+        // shown in `/.status` like any other download
+        if let Some(refreshed) = refreshed {
+            let _ = fs.record_download(refreshed);
+        }
+
+        fs
+    }
+
+    // invalidates the kernel caches when a puzzle unlocks, and the inodes sent by `invalidate_attr`
+    pub fn start_invalidations(&self, notifier: fuser::Notifier) {
+        let (sender, receiver) = mpsc::channel();
+        if self.invalidations.set(sender).is_err() {
+            return;
+        }
+
+        // no filesystem clone, so the thread stops once the filesystem is dropped with the sender
+        let calendar = self.calendar.clone();
+        let clock = self.clock;
+        let layout = self.config.layout();
+        let wait_for_unlock = self.config.wait_for_unlock();
+        thread::spawn(move || {
+            let mut latest = calendar.last_unlocked_puzzle(clock.now());
+            let mut pending = pending_day(&calendar, clock, wait_for_unlock);
+            loop {
+                let timeout = until_listing_change(&calendar, clock, wait_for_unlock);
+                match receiver.recv_timeout(timeout + UNLOCK_GRACE) {
+                    Ok(ino) => {
+                        if let Err(err) = notifier.inval_inode(ino, 0, 0) {
+                            log::debug!("could not invalidate inode {}: {}", ino, err);
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                let unlocked = calendar.last_unlocked_puzzle(clock.now());
+                if unlocked != latest {
+                    invalidate_unlocked(&notifier, layout, unlocked);
+                    latest = unlocked;
+                }
+
+                let next_pending = pending_day(&calendar, clock, wait_for_unlock);
+                if next_pending != pending {
+                    if let Some(day) = next_pending {
+                        invalidate_pending(&notifier, layout, day);
+                    }
+
+                    pending = next_pending;
+                }
+            }
+        });
+    }
+
+    fn invalidate_attr(&self, ino: u64) {
+        if let Some(sender) = self.invalidations.get() {
+            let _ = sender.send(ino);
         }
     }
 
@@ -143,181 +429,875 @@ impl AoCFilesystem {
         }
     }
 
-    fn file_size(&self, day_info: DayAndYear) -> u64 {
-        let path = self.config.cached_day_input(day_info);
+    fn cache_path(&self, day: DayAndYear, file: DayFile) -> PathBuf {
+        match file {
+            DayFile::Input => self.config.cached_day_input(day),
+            DayFile::Puzzle => self.config.cached_day_puzzle(day),
+            DayFile::Example(example) => self.config.cached_day_example(day, example),
+            DayFile::Part(part) => self.config.cached_day_verdict(day, part),
+            DayFile::Answer(part) => self.config.cached_day_answer(day, part),
+        }
+    }
+
+    // `user.aoc.*` extended attributes of `node`, only inputs have some
+    fn xattrs(&self, node: Node) -> Vec<(&'static str, String)> {
+        let day = match node {
+            Node::DayFile(day, DayFile::Input) => day,
+            _ => return Vec::new(),
+        };
+
+        let path = self.config.cached_day_input(day);
+        let cached = path.exists();
+        let mut xattrs = vec![
+            ("user.aoc.year", day.year.to_string()),
+            ("user.aoc.day", day.day.to_string()),
+            ("user.aoc.url", web::puzzle_url(day)),
+            ("user.aoc.cached", cached.to_string()),
+        ];
+
+        if !cached {
+            return xattrs;
+        }
+
+        // inputs cached by older versions have no metadata, the file itself tells the rest
+        match self.input_metadata(day, DayFile::Input) {
+            Some(metadata) => {
+                xattrs.push(("user.aoc.sha256", metadata.sha256));
+                xattrs.push((
+                    "user.aoc.downloaded_at",
+                    metadata.downloaded_at.to_rfc3339(),
+                ));
+            }
+            None => {
+                if let Ok(data) = cache::read(&path) {
+                    xattrs.push(("user.aoc.sha256", cache::sha256(&data)));
+                }
+
+                if let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                    let modified = DateTime::<Utc>::from(modified);
+                    xattrs.push(("user.aoc.downloaded_at", modified.to_rfc3339()));
+                }
+            }
+        }
+
+        xattrs
+    }
+
+    // only inputs have their download recorded, inputs cached by older versions have none
+    fn input_metadata(&self, day: DayAndYear, file: DayFile) -> Option<Metadata> {
+        match file {
+            DayFile::Input => Metadata::load(&self.config.cached_day_input(day)),
+            _ => None,
+        }
+    }
+
+    fn year_file_path(&self, year: u32, file: YearFile) -> PathBuf {
+        match file {
+            YearFile::Stats => self.config.cached_year_stats(year),
+            YearFile::StatsJson => self.config.cached_year_stats_json(year),
+            YearFile::Stars => self.config.cached_year_stars(year),
+            YearFile::StarsJson => self.config.cached_year_stars_json(year),
+        }
+    }
+
+    fn record_download(&self, result: io::Result<()>) -> io::Result<()> {
+        self.status
+            .lock()
+            .expect("status mutex poisoned")
+            .record_download(&result);
+        result
+    }
+
+    fn status_text(&self) -> String {
+        let latest = self.last_unlocked_puzzle();
+        let cached_inputs = self
+            .calendar
+            .years(latest.year)
+            .flat_map(|year| {
+                let max_day = self.max_day(year, latest).unwrap_or(0);
+                (1..=max_day).map(move |day| DayAndYear::new(year, day))
+            })
+            .filter(|&day| self.config.cached_day_input(day).exists())
+            .count();
+
+        self.status.lock().expect("status mutex poisoned").render(
+            &self.config.cache_dir().to_string_lossy(),
+            cached_inputs,
+            self.next_unlock(),
+        )
+    }
+
+    fn next_unlock_text(&self) -> String {
+        let (day, unlock) = self.next_unlock();
+        let remaining = unlock
+            .signed_duration_since(self.clock.now())
+            .num_seconds()
+            .max(0);
+        format!(
+            "puzzle: {}/day{:02}\nunlock: {}\ntimestamp: {}\nremaining: {}\n",
+            day.year,
+            day.day,
+            unlock.to_rfc3339(),
+            unlock.timestamp(),
+            remaining
+        )
+    }
+
+    fn last_unlocked_puzzle(&self) -> DayAndYear {
+        self.calendar.last_unlocked_puzzle(self.clock.now())
+    }
+
+    fn next_unlock(&self) -> (DayAndYear, DateTime<FixedOffset>) {
+        self.calendar.next_unlock(self.clock.now())
+    }
+
+    fn until_listing_change(&self) -> Duration {
+        until_listing_change(&self.calendar, self.clock, self.config.wait_for_unlock())
+    }
+
+    // how long the kernel may cache the attributes and the entry of `node`
+    fn ttl(&self, node: Node) -> Duration {
+        match node {
+            // only change when a puzzle unlocks
+            Node::Root
+            | Node::LatestYear
+            | Node::Year(_)
+            | Node::LatestDay(_)
+            | Node::DayDir(_)
+            | Node::Next => self.until_listing_change(),
+            // released inputs and accepted answers never change once cached
+            Node::DayFile(day, file @ (DayFile::Input | DayFile::Answer(_)))
+                if self.cache_path(day, file).exists() =>
+            {
+                MAX_TTL
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    // whether `node` belongs to a puzzle that isn't released yet (and won't be before the next unlock)
+    fn is_unreleased(&self, node: Node) -> bool {
+        let latest = self.last_unlocked_puzzle();
+        match node {
+            Node::Year(year) => year > latest.year,
+            Node::DayDir(day) | Node::DayFile(day, _) => day > latest,
+            _ => false,
+        }
+    }
+
+    fn handles(&self) -> MutexGuard<'_, HashMap<u64, Handle>> {
+        self.handles.lock().expect("handles mutex poisoned")
+    }
+
+    fn contents(&self) -> MutexGuard<'_, HashMap<u64, Arc<[u8]>>> {
+        self.contents.lock().expect("contents mutex poisoned")
+    }
+
+    fn download_lock(&self, path: &Path) -> DownloadLock {
+        let mut downloads = self.downloads.lock().expect("downloads mutex poisoned");
+        downloads.entry(path.to_path_buf()).or_default().clone()
+    }
+
+    fn insert_handle(&self, handle: Handle) -> u64 {
+        let fh = self.next_fh.fetch_add(1, Ordering::Relaxed);
+        self.handles().insert(fh, handle);
+        fh
+    }
+
+    fn pending_day(&self) -> Option<DayAndYear> {
+        pending_day(&self.calendar, self.clock, self.config.wait_for_unlock())
+    }
+
+    // number of examples of `day`, `None` if its puzzle isn't cached yet
+    fn example_count(&self, day: DayAndYear) -> Option<u8> {
+        if !self.config.cached_day_puzzle(day).exists() {
+            return None;
+        }
+
+        let count = (1..=MAX_EXAMPLES)
+            .take_while(|&example| self.config.cached_day_example(day, example).exists())
+            .count();
+        Some(count as u8)
+    }
+
+    fn day_files(&self, day: DayAndYear) -> Vec<DayFile> {
+        if self.pending_day() == Some(day) {
+            return vec![DayFile::Input, DayFile::Puzzle];
+        }
+
+        let examples = self.example_count(day).unwrap_or(0);
+        [DayFile::Input, DayFile::Puzzle]
+            .into_iter()
+            .chain((1..=examples).map(DayFile::Example))
+            .chain([DayFile::Part(1), DayFile::Part(2)])
+            .chain(
+                [DayFile::Answer(1), DayFile::Answer(2)]
+                    .into_iter()
+                    .filter(|&file| self.cache_path(day, file).exists()),
+            )
+            .collect()
+    }
 
-        match fs::metadata(path) {
-            Ok(metadata) => metadata.size(),
+    fn read_verdict(&self, day: DayAndYear, part: u8) -> Vec<u8> {
+        let path = self.config.cached_day_verdict(day, part);
+        match fs::read(&path) {
+            Ok(verdict) => verdict,
             Err(err) => {
                 if err.kind() != io::ErrorKind::NotFound {
-                    log::warn!("cache_dir metadata error: {}", err);
+                    log::warn!("error reading {:?}: {}", path, err);
                 }
 
-                4096
+                Vec::new()
             }
         }
     }
 
-    fn getattr_impl(&self, ino: u64) -> Result<(Duration, FileAttr), libc::c_int> {
-        let latest = DayAndYear::last_unlocked_puzzle();
-        let day_info = DayAndYear::from_ino(ino);
-        if day_info.year < AOC_FIRST_YEAR {
-            if ino == LATEST_ROOT_INO {
-                let mut attr = self.getattr_template(ino);
-                attr.kind = fuser::FileType::Symlink;
-                attr.size = latest.year.to_string().len() as u64;
-                return Ok((Duration::ZERO, attr));
+    fn submit_answer(&self, day: DayAndYear, part: u8, answer: &str) -> Result<(), libc::c_int> {
+        log::info!(
+            "submitting {:?} for {}/day{:02} part {}",
+            answer,
+            day.year,
+            day.day,
+            part
+        );
+
+        let page = match web::submit_answer(day, part, answer, self.config.session_token()) {
+            Ok(page) => page,
+            Err(err) => return Err(errno(&err)),
+        };
+
+        let verdict = puzzle::verdict(&page);
+        log::info!(
+            "{}/day{:02} part {}: {}",
+            day.year,
+            day.day,
+            part,
+            verdict.trim_end()
+        );
+
+        let path = self.config.cached_day_verdict(day, part);
+        create_parent_dir(&path)?;
+        if let Err(err) = fs::write(&path, &verdict) {
+            log::error!("Failed to write {:?}: {}", path, err);
+            return Err(err.raw_os_error().unwrap_or(libc::EIO));
+        }
+
+        if verdict.starts_with("correct\n") {
+            // the puzzle page, stats and stars change once a part is solved
+            let _ = fs::remove_file(self.config.cached_day_puzzle(day));
+            for file in YearFile::ALL {
+                let _ = fs::remove_file(self.year_file_path(day.year, file));
             }
+        }
 
-            return Err(libc::ENOENT);
+        Ok(())
+    }
+
+    // downloads the puzzle page if it isn't cached, only opening `dayNN.md` refreshes it
+    fn ensure_puzzle(&self, day: DayAndYear) -> Result<(), libc::c_int> {
+        let path = self.config.cached_day_puzzle(day);
+        let node = Node::DayFile(day, DayFile::Puzzle);
+        self.open_cached(node, &path, None, || puzzle::download(&self.config, day))
+            .map(|_| ())
+    }
+
+    // whether every answer of `day` is cached, the last day of an event has no part 2 answer
+    fn is_solved(&self, day: DayAndYear) -> bool {
+        let last_part = if self.calendar.days(day.year) == Some(day.day) {
+            1
+        } else {
+            2
+        };
+
+        self.config.cached_day_answer(day, last_part).exists()
+    }
+
+    fn file_size(&self, day_info: DayAndYear, file: DayFile) -> u64 {
+        match cached_file_size(&self.cache_path(day_info, file)) {
+            Some(size) => size,
+            // nothing was submitted yet
+            None if matches!(file, DayFile::Part(_)) => 0,
+            None => 4096,
         }
+    }
 
-        if day_info > latest && !(day_info.year == latest.year && day_info.day == 26) {
-            return Err(libc::ENOENT);
+    // last released day of `year`, `None` if no puzzle of that year is released yet
+    fn max_day(&self, year: u32, latest: DayAndYear) -> Option<u8> {
+        self.calendar.released_days(year, latest)
+    }
+
+    fn is_released(&self, day: DayAndYear) -> bool {
+        let latest = self.last_unlocked_puzzle();
+        self.max_day(day.year, latest)
+            .is_some_and(|max| day.day <= max)
+    }
+
+    fn node_exists(&self, node: Node) -> bool {
+        let latest = self.last_unlocked_puzzle();
+        let pending = self.pending_day();
+        match node {
+            Node::Root | Node::LatestYear | Node::Status | Node::Next | Node::NextUnlock => true,
+            Node::Year(year) => {
+                self.max_day(year, latest).is_some() || pending.is_some_and(|day| day.year == year)
+            }
+            Node::LatestDay(year) => self.max_day(year, latest).is_some(),
+            Node::DayDir(day) => {
+                self.config.layout() == Layout::PerDay
+                    && (pending == Some(day) || self.is_released(day))
+            }
+            Node::DayFile(day, file) => {
+                let released = self.is_released(day);
+                match file {
+                    DayFile::Input | DayFile::Puzzle if pending == Some(day) => true,
+                    DayFile::Example(example) => {
+                        released
+                            && self
+                                .example_count(day)
+                                .is_some_and(|count| example <= count)
+                    }
+                    DayFile::Answer(part) => {
+                        released && self.config.cached_day_answer(day, part).exists()
+                    }
+                    DayFile::Input | DayFile::Puzzle | DayFile::Part(_) => released,
+                }
+            }
+            Node::YearFile(year, _) => self.max_day(year, latest).is_some(),
+            Node::Leaderboards => !self.config.leaderboards().is_empty(),
+            Node::Leaderboard(board) => (board as usize) < self.config.leaderboards().len(),
+            Node::LeaderboardJson(board, year) | Node::LeaderboardTable(board, year) => {
+                (board as usize) < self.config.leaderboards().len()
+                    && self.max_day(year, latest).is_some()
+            }
         }
+    }
+
+    fn leaderboard_path(&self, board: u32, year: u32) -> PathBuf {
+        let id = self.config.leaderboards()[board as usize];
+        self.config.cached_leaderboard(id, year)
+    }
+
+    fn getattr_impl(&self, ino: u64) -> Result<(Duration, FileAttr), libc::c_int> {
+        let node = match Node::from_ino(ino) {
+            Some(node) if self.node_exists(node) => node,
+            _ => return Err(libc::ENOENT),
+        };
 
         let mut attr = self.getattr_template(ino);
-        match day_info.file_type()? {
-            fuser::FileType::RegularFile => {
-                attr.blksize = 4096;
-                attr.size = self.file_size(day_info);
-                attr.blocks = 1;
+        attr.kind = node.file_type();
+        match node {
+            Node::Root => {
+                let latest = self.last_unlocked_puzzle();
+                attr.perm = 0o555;
+                attr.nlink = 2 + self.calendar.years(latest.year).count() as u32;
+
+                return Ok((self.ttl(node), attr));
+            }
+            Node::Year(year) => {
+                attr.perm = 0o555;
+                attr.nlink = 2;
+                if self.config.layout() == Layout::PerDay {
+                    let latest = self.last_unlocked_puzzle();
+                    attr.nlink += self.max_day(year, latest).unwrap_or(0) as u32;
+                }
+
+                let unlock = self.calendar.unlock_time(DayAndYear::new(year, 1));
+                set_times(&mut attr, unlock, None);
+            }
+            Node::DayDir(day) => {
+                attr.perm = 0o555;
+                attr.nlink = 2;
+                set_times(&mut attr, self.calendar.unlock_time(day), None);
             }
-            fuser::FileType::Directory => {
-                attr.kind = fuser::FileType::Directory;
+            Node::Leaderboards | Node::Leaderboard(_) => {
                 attr.perm = 0o555;
                 attr.nlink = 2;
             }
-            fuser::FileType::Symlink => {
-                attr.kind = fuser::FileType::Symlink;
+            Node::LatestYear | Node::LatestDay(_) | Node::Next => {
                 attr.perm = 0o777;
-                attr.size = 9;
+                attr.size = self.readlink_impl(node)?.len() as u64;
+            }
+            Node::DayFile(day, file) => {
+                if let DayFile::Part(_) = file {
+                    attr.perm = 0o644;
+                }
+
+                attr.blksize = 4096;
+                attr.size = self.file_size(day, file);
+                attr.blocks = 1;
+                let unlock = self.calendar.unlock_time(day);
+                let path = self.cache_path(day, file);
+                set_times(&mut attr, unlock, Some(&path));
+                if let Some(metadata) = self.input_metadata(day, file) {
+                    attr.mtime = metadata.downloaded_at.into();
+                    attr.atime = attr.mtime;
+                }
+            }
+            Node::Status => {
+                attr.size = self.status_text().len() as u64;
+            }
+            Node::NextUnlock => {
+                attr.size = self.next_unlock_text().len() as u64;
+            }
+            Node::YearFile(year, file) => {
+                attr.blksize = 4096;
+                let path = self.year_file_path(year, file);
+                attr.size = cached_file_size(&path).unwrap_or(4096);
+                attr.blocks = 1;
+                let unlock = self.calendar.unlock_time(DayAndYear::new(year, 1));
+                set_times(&mut attr, unlock, Some(&path));
+            }
+            Node::LeaderboardJson(board, year) => {
+                attr.blksize = 4096;
+                let path = self.leaderboard_path(board, year);
+                attr.size = cached_file_size(&path).unwrap_or(4096);
+                attr.blocks = 1;
+                let unlock = self.calendar.unlock_time(DayAndYear::new(year, 1));
+                set_times(&mut attr, unlock, Some(&path));
+            }
+            Node::LeaderboardTable(board, year) => {
+                // the table is only rendered from the JSON already in the cache
+                attr.blksize = 4096;
+                let path = self.leaderboard_path(board, year);
+                attr.size = cache::read(&path)
+                    .ok()
+                    .and_then(|json| leaderboard::render(&json).ok())
+                    .map_or(4096, |table| table.len() as u64);
+                attr.blocks = 1;
+                let unlock = self.calendar.unlock_time(DayAndYear::new(year, 1));
+                set_times(&mut attr, unlock, Some(&path));
             }
-            _ => unreachable!("File type was neither Directory, RegularFile nor Symlink"),
         }
 
-        Ok((Duration::ZERO, attr))
+        Ok((self.ttl(node), attr))
     }
 
-    fn lookup_year(&self, year: u32, name: &str) -> Result<u64, libc::c_int> {
-        let latest = DayAndYear::last_unlocked_puzzle();
-        if year < AOC_FIRST_YEAR || year > latest.year {
-            return Err(libc::ENOENT);
+    fn lookup_impl(&self, parent: u64, name: &str) -> Result<Node, libc::c_int> {
+        match Node::from_ino(parent) {
+            Some(Node::Root) => self.lookup_root(name),
+            Some(Node::Year(year)) => self.lookup_year(year, name),
+            Some(Node::DayDir(day)) => self.lookup_day(day, name),
+            Some(Node::Leaderboards) => self.lookup_leaderboards(name),
+            Some(Node::Leaderboard(board)) => self.lookup_leaderboard(board, name),
+            Some(_) => Err(libc::ENOTDIR),
+            None => Err(libc::ENOENT),
         }
+    }
 
-        let name = name.trim_end_matches(".txt").trim_end_matches(".input");
-        if name == "latest" {
-            return Ok(DayAndYear::new(year, 26).to_ino());
+    fn lookup_year(&self, year: u32, name: &str) -> Result<Node, libc::c_int> {
+        if let Some(file) = YearFile::ALL
+            .into_iter()
+            .find(|file| file.file_name() == name)
+        {
+            return Ok(Node::YearFile(year, file));
         }
 
-        let name = name.trim_start_matches("day").trim_start_matches('0');
-        let day = match name.parse::<u8>() {
-            Ok(day) => day,
-            Err(_) => return Err(libc::ENOENT),
-        };
+        if self.config.layout() == Layout::PerDay {
+            if name == "latest" {
+                return Ok(Node::LatestDay(year));
+            }
 
-        if year < AOC_FIRST_YEAR_WITH_12_DAYS {
-            if !matches!(day, 1..=25) {
-                return Err(libc::ENOENT);
+            let day = parse_day(name).ok_or(libc::ENOENT)?;
+            return Ok(Node::DayDir(DayAndYear::new(year, day)));
+        }
+
+        let (name, file) = match name.strip_suffix(".md") {
+            Some(name) => (
+                name.trim_end_matches(".md").trim_end_matches(".puzzle"),
+                DayFile::Puzzle,
+            ),
+            None => {
+                let name = name.trim_end_matches(".txt").trim_end_matches(".input");
+                if let Some((name, example)) = name.rsplit_once(".example") {
+                    (name, DayFile::Example(parse_example(example)?))
+                } else if let Some((name, part)) = name.rsplit_once(".part") {
+                    (name, DayFile::Part(parse_part(part)?))
+                } else if let Some((name, part)) = name.rsplit_once(".answer") {
+                    (name, DayFile::Answer(parse_part(part)?))
+                } else {
+                    (name, DayFile::Input)
+                }
             }
+        };
+
+        if name == "latest" && file == DayFile::Input {
+            return Ok(Node::LatestDay(year));
+        }
+
+        let day = parse_day(name).ok_or(libc::ENOENT)?;
+        Ok(Node::DayFile(DayAndYear::new(year, day), file))
+    }
+
+    fn lookup_day(&self, day: DayAndYear, name: &str) -> Result<Node, libc::c_int> {
+        let file = if name.trim_end_matches(".txt") == "input" {
+            DayFile::Input
+        } else if name.trim_end_matches(".md") == "puzzle" {
+            DayFile::Puzzle
+        } else if let Some(example) = name.trim_end_matches(".txt").strip_prefix("example") {
+            DayFile::Example(parse_example(example)?)
+        } else if let Some(part) = name.strip_prefix("part") {
+            DayFile::Part(parse_part(part)?)
+        } else if let Some(part) = name.strip_prefix("answer") {
+            DayFile::Answer(parse_part(part)?)
         } else {
-            if !matches!(day, 1..=12) {
-                return Err(libc::ENOENT);
-            }
+            return Err(libc::ENOENT);
+        };
+
+        Ok(Node::DayFile(day, file))
+    }
+
+    fn lookup_leaderboards(&self, name: &str) -> Result<Node, libc::c_int> {
+        let id = name.parse::<u64>().map_err(|_| libc::ENOENT)?;
+        match self
+            .config
+            .leaderboards()
+            .iter()
+            .position(|&board| board == id)
+        {
+            Some(board) => Ok(Node::Leaderboard(board as u32)),
+            None => Err(libc::ENOENT),
         }
+    }
 
-        Ok(DayAndYear::new(year, day).to_ino())
+    fn lookup_leaderboard(&self, board: u32, name: &str) -> Result<Node, libc::c_int> {
+        if let Some(Ok(year)) = name.strip_suffix(".json").map(str::parse::<u32>) {
+            return Ok(Node::LeaderboardJson(board, year));
+        }
+
+        match name.strip_suffix(".txt").map(str::parse::<u32>) {
+            Some(Ok(year)) => Ok(Node::LeaderboardTable(board, year)),
+            _ => Err(libc::ENOENT),
+        }
     }
 
-    fn lookup_root(&self, name: &str) -> Result<u64, libc::c_int> {
+    fn lookup_root(&self, name: &str) -> Result<Node, libc::c_int> {
         if name == "latest" {
-            return Ok(LATEST_ROOT_INO);
+            return Ok(Node::LatestYear);
+        } else if name == "leaderboards" {
+            return Ok(Node::Leaderboards);
+        } else if name == ".status" {
+            return Ok(Node::Status);
+        } else if name == "next" {
+            return Ok(Node::Next);
+        } else if name == "next_unlock" {
+            return Ok(Node::NextUnlock);
         }
 
-        let year = match name.parse::<u32>() {
-            Ok(res) => res,
-            Err(_) => return Err(libc::ENOENT),
+        match name.parse::<u32>() {
+            Ok(year) => Ok(Node::Year(year)),
+            Err(_) => Err(libc::ENOENT),
+        }
+    }
+
+    fn readlink_impl(&self, node: Node) -> Result<String, libc::c_int> {
+        let latest = self.last_unlocked_puzzle();
+        let year = match node {
+            Node::LatestYear => return Ok(latest.year.to_string()),
+            Node::Next => {
+                let (next, _) = self.next_unlock();
+                let layout = self.config.layout();
+                let name = match layout {
+                    Layout::Flat => DayFile::Input.file_name(next.day, layout),
+                    Layout::PerDay => day_dir_name(next.day),
+                };
+
+                return Ok(format!("{}/{}", next.year, name));
+            }
+            Node::LatestDay(year) => year,
+            _ => return Err(libc::EINVAL),
         };
 
-        let latest = DayAndYear::last_unlocked_puzzle();
-        if year < AOC_FIRST_YEAR || year > latest.year {
-            Err(libc::ENOENT)
-        } else {
-            Ok(DayAndYear::new(year, 0).to_ino())
+        let day = self.max_day(year, latest).ok_or(libc::ENOENT)?;
+        let layout = self.config.layout();
+        Ok(match layout {
+            Layout::Flat => DayFile::Input.file_name(day, layout),
+            Layout::PerDay => day_dir_name(day),
+        })
+    }
+
+    fn dir_entries(&self, node: Node) -> Result<Vec<(Node, String)>, libc::c_int> {
+        let latest = self.last_unlocked_puzzle();
+        let layout = self.config.layout();
+        let mut entries = vec![(node, ".".to_string())];
+
+        match node {
+            Node::Root => {
+                entries.push((Node::Root, "..".into()));
+                let pending_year = self
+                    .pending_day()
+                    .map(|day| day.year)
+                    .filter(|&year| year > latest.year);
+                for year in self.calendar.years(latest.year).chain(pending_year) {
+                    entries.push((Node::Year(year), year.to_string()));
+                }
+
+                entries.push((Node::LatestYear, "latest".into()));
+                entries.push((Node::Next, "next".into()));
+                entries.push((Node::NextUnlock, "next_unlock".into()));
+                entries.push((Node::Status, ".status".into()));
+                if self.node_exists(Node::Leaderboards) {
+                    entries.push((Node::Leaderboards, "leaderboards".into()));
+                }
+            }
+            Node::Year(year) => {
+                entries.push((Node::Root, "..".into()));
+                let max_day = self.max_day(year, latest);
+                let pending = self.pending_day().filter(|day| day.year == year);
+                let days = (1..=max_day.unwrap_or(0))
+                    .map(|day| DayAndYear::new(year, day))
+                    .chain(pending);
+                for day in days {
+                    match layout {
+                        Layout::Flat => {
+                            for file in self.day_files(day) {
+                                entries.push((
+                                    Node::DayFile(day, file),
+                                    file.file_name(day.day, layout),
+                                ));
+                            }
+                        }
+                        Layout::PerDay => entries.push((Node::DayDir(day), day_dir_name(day.day))),
+                    }
+                }
+
+                // a year whose first puzzle is still pending has nothing else
+                if max_day.is_some() {
+                    for file in YearFile::ALL {
+                        entries.push((Node::YearFile(year, file), file.file_name().into()));
+                    }
+
+                    entries.push((Node::LatestDay(year), "latest".into()));
+                }
+            }
+            Node::DayDir(day) => {
+                entries.push((Node::Year(day.year), "..".into()));
+                for file in self.day_files(day) {
+                    entries.push((Node::DayFile(day, file), file.file_name(day.day, layout)));
+                }
+            }
+            Node::Leaderboards => {
+                entries.push((Node::Root, "..".into()));
+                for (board, id) in self.config.leaderboards().iter().enumerate() {
+                    entries.push((Node::Leaderboard(board as u32), id.to_string()));
+                }
+            }
+            Node::Leaderboard(board) => {
+                entries.push((Node::Leaderboards, "..".into()));
+                for year in self.calendar.years(latest.year) {
+                    entries.push((Node::LeaderboardJson(board, year), format!("{year}.json")));
+                    entries.push((Node::LeaderboardTable(board, year), format!("{year}.txt")));
+                }
+            }
+            Node::LatestYear
+            | Node::LatestDay(_)
+            | Node::DayFile(..)
+            | Node::YearFile(..)
+            | Node::Status
+            | Node::Next
+            | Node::NextUnlock
+            | Node::LeaderboardJson(..)
+            | Node::LeaderboardTable(..) => return Err(libc::ENOTDIR),
         }
+
+        Ok(entries)
     }
 
-    fn readlink_impl(&self, ino: u64) -> Result<String, libc::c_int> {
-        if ino == LATEST_ROOT_INO {
-            let latest = DayAndYear::last_unlocked_puzzle();
-            return Ok(latest.year.to_string());
+    fn open_day_file(&self, day: DayAndYear, file: DayFile) -> Result<File, libc::c_int> {
+        log::trace!(
+            "open(\"{}/{}\")",
+            day.year,
+            file.file_name(day.day, Layout::Flat)
+        );
+
+        // the page gets part 2 and the answers once the parts are solved, which may happen after
+        // it was cached
+        let max_age = match file {
+            DayFile::Puzzle if !self.is_solved(day) => Some(ANSWER_REFRESH_INTERVAL),
+            _ => None,
+        };
+
+        let input_path = self.cache_path(day, file);
+        self.open_cached(
+            Node::DayFile(day, file),
+            &input_path,
+            max_age,
+            || match file {
+                DayFile::Input => web::download_input(
+                    day,
+                    &input_path,
+                    self.config.session_token(),
+                    self.config.username(),
+                    self.config.compress(),
+                ),
+                DayFile::Puzzle | DayFile::Example(_) | DayFile::Answer(_) => {
+                    puzzle::download(&self.config, day)
+                }
+                DayFile::Part(_) => unreachable!("answer files are never downloaded"),
+            },
+        )
+    }
+
+    // contents of a day file, downloading it first if needed
+    fn day_file_contents(&self, day: DayAndYear, file: DayFile) -> Result<Arc<[u8]>, libc::c_int> {
+        let immutable = matches!(file, DayFile::Input | DayFile::Answer(_));
+        let ino = Node::DayFile(day, file).ino();
+        if immutable {
+            if let Some(data) = self.contents().get(&ino) {
+                return Ok(data.clone());
+            }
         }
 
-        let year = (ino / 100) as u32;
-        let day = ino % 100;
-        if day != 26 {
-            return Err(libc::EINVAL);
+        let data = load(self.open_day_file(day, file)?)?;
+        if immutable {
+            self.contents().insert(ino, data.clone());
         }
 
-        let latest = DayAndYear::last_unlocked_puzzle();
-        if year < AOC_FIRST_YEAR || year > latest.year {
-            return Err(libc::ENOENT);
+        Ok(data)
+    }
+
+    fn open_year_file(&self, year: u32, file: YearFile) -> Result<File, libc::c_int> {
+        log::trace!("open(\"{}/{}\")", year, file.file_name());
+
+        let path = self.year_file_path(year, file);
+        match file {
+            YearFile::Stats | YearFile::StatsJson => {
+                let node = Node::YearFile(year, file);
+                self.open_cached(node, &path, Some(STATS_REFRESH_INTERVAL), || {
+                    stats::download(&self.config, year)
+                })
+            }
+            YearFile::Stars | YearFile::StarsJson => {
+                let node = Node::YearFile(year, file);
+                self.open_cached(node, &path, Some(STARS_REFRESH_INTERVAL), || {
+                    stars::download(&self.config, year)
+                })
+            }
         }
+    }
 
-        let day = if latest.year == year {
-            latest.day
-        } else {
-            if year < AOC_FIRST_YEAR_WITH_12_DAYS {
-                25
-            } else {
-                12
+    // replies from another thread once `day` is unlocked, so the rest of the filesystem keeps working
+    fn open_when_unlocked(&self, day: DayAndYear, file: DayFile, reply: fuser::ReplyOpen) {
+        // the unlock may have passed since the pending day was checked
+        let wait = self
+            .calendar
+            .unlock_time(day)
+            .signed_duration_since(self.clock.now())
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+            + UNLOCK_GRACE;
+        log::info!(
+            "waiting {:?} for {}/day{:02} to unlock",
+            wait,
+            day.year,
+            day.day
+        );
+
+        let fs = self.clone();
+        thread::spawn(move || {
+            thread::sleep(wait);
+            match fs.day_file_contents(day, file) {
+                Ok(data) => {
+                    let fh = fs.insert_handle(Handle::Data(data));
+                    reply.opened(fh, 0);
+                }
+                Err(err) => reply.error(err),
             }
-        };
+        });
+    }
 
-        Ok(format!("day{day:02}.txt"))
+    fn open_leaderboard(&self, board: u32, year: u32) -> Result<File, libc::c_int> {
+        let id = self.config.leaderboards()[board as usize];
+        log::trace!("open(\"leaderboards/{id}/{year}.json\")");
+
+        let path = self.leaderboard_path(board, year);
+        let node = Node::LeaderboardJson(board, year);
+        self.open_cached(node, &path, Some(LEADERBOARD_REFRESH_INTERVAL), || {
+            web::download_leaderboard(
+                id,
+                year,
+                &path,
+                self.config.session_token(),
+                self.config.compress(),
+            )
+        })
     }
 
-    fn open_day_input(&self, day: DayAndYear) -> Result<File, libc::c_int> {
-        log::trace!("open(\"{}/day{:02}.txt\")", day.year, day.day);
+    fn render_leaderboard(&self, board: u32, year: u32) -> Result<String, libc::c_int> {
+        let json = match cache::read_from(&mut self.open_leaderboard(board, year)?) {
+            Ok(json) => json,
+            Err(err) => {
+                log::error!("error reading leaderboard: {}", err);
+                return Err(err.raw_os_error().unwrap_or(libc::EIO));
+            }
+        };
+
+        match leaderboard::render(&json) {
+            Ok(table) => Ok(table),
+            Err(err) => {
+                let path = self.leaderboard_path(board, year);
+                log::error!("invalid leaderboard JSON in {:?}: {}", path, err);
+                Err(libc::EIO)
+            }
+        }
+    }
 
-        let input_path = self.config.cached_day_input(day);
-        match File::options().read(true).open(&input_path) {
-            Ok(f) => return Ok(f),
+    // opens `path` from the cache, calling `download` first if it is missing or older than `max_age`
+    // (the outdated version is still used if downloading fails). With a `max_age`, a failed download
+    // isn't retried sooner than a successful one, the error is returned again until then.
+    fn open_cached<F>(
+        &self,
+        node: Node,
+        path: &Path,
+        max_age: Option<Duration>,
+        download: F,
+    ) -> Result<File, libc::c_int>
+    where
+        F: FnOnce() -> io::Result<()>,
+    {
+        let lock = self.download_lock(path);
+        let mut last_failure = lock.lock().expect("download mutex poisoned");
+        let outdated = match File::options().read(true).open(path) {
+            Ok(f) if is_empty(&f) => None,
+            Ok(f) => match max_age {
+                Some(max_age) if is_older_than(&f, max_age) => Some(f),
+                _ => return Ok(f),
+            },
             Err(e) => {
                 if e.kind() != std::io::ErrorKind::NotFound {
-                    log::error!("error opening {:?}: {}", input_path, e);
+                    log::error!("error opening {:?}: {}", path, e);
                     return Err(e
                         .raw_os_error()
                         .expect("File::open() => Err(e) => e.raw_os_error()"));
                 }
+
+                None
             }
-        }
+        };
 
-        let parent_input_path = input_path.parent().expect("No parent for input path???");
-        if !parent_input_path.exists() {
-            match fs::create_dir_all(parent_input_path) {
-                Ok(()) => (),
-                Err(e) => {
-                    log::error!(
-                        "Could not create cache directory {:?}: {}",
-                        parent_input_path,
-                        e
-                    );
-                    return Err(e
-                        .raw_os_error()
-                        .expect("fs::create_dir_all() => Err(e) => e.raw_os_error()"));
-                }
+        if let (Some(max_age), Some((failed_at, err))) = (max_age, *last_failure) {
+            if failed_at.elapsed() < max_age {
+                return outdated.ok_or(err);
             }
         }
 
-        if let Err(err) = web::download_input(day, &input_path, self.config.session_token()) {
-            return Err(err.raw_os_error().expect("no os error"));
+        create_parent_dir(path)?;
+        if let Err(err) = self.record_download(download()) {
+            *last_failure = Some((Instant::now(), errno(&err)));
+            if let Some(f) = outdated {
+                log::warn!("using outdated {:?}", path);
+                return Ok(f);
+            }
+
+            return Err(errno(&err));
         }
 
-        match File::options().read(true).open(&input_path) {
+        *last_failure = None;
+
+        // the kernel may still have the size of the placeholder or of the outdated version
+        self.invalidate_attr(node.ino());
+        match File::options().read(true).open(path) {
             Ok(f) => Ok(f),
             Err(e) => {
-                log::error!("error opening {:?} after downloading it: {}", input_path, e);
+                log::error!("error opening {:?} after downloading it: {}", path, e);
                 Err(e
                     .raw_os_error()
                     .expect("File::open() => Err(e) => e.raw_os_error()"))
@@ -356,32 +1336,34 @@ impl fuser::Filesystem for AoCFilesystem {
         };
 
         log::trace!("lookup(..., parent={parent}, name={name:?})");
-        let ino = if parent == fuser::FUSE_ROOT_ID {
-            match self.lookup_root(name) {
-                Ok(ino) => ino,
-                Err(e) => {
-                    reply.error(e);
-                    return;
-                }
-            }
-        } else {
-            let year = parent / 100;
-            if parent % 100 != 0 || parent == LATEST_ROOT_INO {
-                reply.error(libc::ENOTDIR);
+        let node = match self.lookup_impl(parent, name) {
+            Ok(node) => node,
+            Err(e) => {
+                reply.error(e);
                 return;
             }
+        };
 
-            match self.lookup_year(year as u32, name) {
-                Ok(ino) => ino,
-                Err(e) => {
-                    reply.error(e);
-                    return;
-                }
+        // examples and answers are only known once the puzzle page has been downloaded
+        if let Node::DayFile(day, file) = node {
+            let released = self.is_released(day);
+            let fetched = match file {
+                DayFile::Example(_) | DayFile::Answer(_) if released => self.ensure_puzzle(day),
+                _ => Ok(()),
+            };
+
+            if let Err(e) = fetched {
+                reply.error(e);
+                return;
             }
-        };
+        }
 
-        let (ttl, attr) = match self.getattr_impl(ino) {
+        let (ttl, attr) = match self.getattr_impl(node.ino()) {
             Ok(res) => res,
+            // negative entry (inode 0), the kernel won't ask again before the listings change
+            Err(libc::ENOENT) if self.is_unreleased(node) => {
+                (self.until_listing_change(), self.getattr_template(0))
+            }
             Err(e) => {
                 reply.error(e);
                 return;
@@ -400,28 +1382,73 @@ impl fuser::Filesystem for AoCFilesystem {
     ) {
         log::trace!("getattr(..., ino={ino}, fh={fh:?})");
 
-        match ino {
-            fuser::FUSE_ROOT_ID => {
-                let latest = DayAndYear::last_unlocked_puzzle();
-                let mut attr = self.getattr_template(fuser::FUSE_ROOT_ID);
-                attr.kind = fuser::FileType::Directory;
-                attr.perm = 0o555;
-                attr.nlink = 2 + (latest.year - AOC_FIRST_YEAR);
+        match self.getattr_impl(ino) {
+            Ok((ttl, attr)) => reply.attr(&ttl, &attr),
+            Err(errno_val) => {
+                log::warn!("getattr received a request for ino {ino}, error occurred (errno = {errno_val})");
+                reply.error(errno_val);
+            }
+        }
+    }
+
+    fn getxattr(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        name: &OsStr,
+        size: u32,
+        reply: fuser::ReplyXattr,
+    ) {
+        log::trace!("getxattr(..., ino={ino}, name={name:?}, size={size})");
 
-                reply.attr(&Duration::from_secs(1), &attr);
+        let node = match Node::from_ino(ino) {
+            Some(node) if self.node_exists(node) => node,
+            _ => {
+                reply.error(libc::ENOENT);
+                return;
             }
-            other => match self.getattr_impl(other) {
-                Ok((ttl, attr)) => reply.attr(&ttl, &attr),
-                Err(errno_val) => {
-                    log::warn!("getattr received a request for ino {other}, error occurred (errno = {errno_val})");
-                    reply.error(errno_val);
-                }
-            },
+        };
+
+        match self.xattrs(node).into_iter().find(|(key, _)| name == *key) {
+            Some((_, value)) => reply_xattr(reply, value.as_bytes(), size),
+            None => reply.error(libc::ENODATA),
+        }
+    }
+
+    fn listxattr(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        size: u32,
+        reply: fuser::ReplyXattr,
+    ) {
+        log::trace!("listxattr(..., ino={ino}, size={size})");
+
+        let node = match Node::from_ino(ino) {
+            Some(node) if self.node_exists(node) => node,
+            _ => {
+                reply.error(libc::ENOENT);
+                return;
+            }
+        };
+
+        // names are each followed by a NUL byte
+        let mut names = Vec::new();
+        for (key, _) in self.xattrs(node) {
+            names.extend_from_slice(key.as_bytes());
+            names.push(0);
         }
+
+        reply_xattr(reply, &names, size);
     }
 
     fn readlink(&mut self, _req: &fuser::Request<'_>, ino: u64, reply: fuser::ReplyData) {
-        match self.readlink_impl(ino) {
+        let link = match Node::from_ino(ino) {
+            Some(node) if self.node_exists(node) => self.readlink_impl(node),
+            _ => Err(libc::ENOENT),
+        };
+
+        match link {
             Ok(link) => {
                 log::trace!("readlink(..., ino={}) => {:?}", ino, &link);
                 reply.data(link.as_bytes());
@@ -443,163 +1470,397 @@ impl fuser::Filesystem for AoCFilesystem {
     ) {
         log::trace!("readdir(..., ino={ino}, offset={offset})");
 
-        let latest = DayAndYear::last_unlocked_puzzle();
-        if ino == fuser::FUSE_ROOT_ID {
-            if offset == 0 && reply.add(ino, 1, fuser::FileType::Directory, ".") {
-                reply.ok();
+        let entries = match Node::from_ino(ino) {
+            Some(node) if self.node_exists(node) => self.dir_entries(node),
+            _ => Err(libc::ENOENT),
+        };
+
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                reply.error(err);
                 return;
             }
+        };
 
-            if offset <= 1 && reply.add(fuser::FUSE_ROOT_ID, 2, fuser::FileType::Directory, "..") {
-                reply.ok();
+        let skipped = usize::try_from(offset).unwrap_or(0);
+        for (i, (node, name)) in entries.iter().enumerate().skip(skipped) {
+            if reply.add(node.ino(), (i + 1) as i64, node.file_type(), name) {
+                break;
+            }
+        }
+
+        reply.ok();
+    }
+
+    fn open(&mut self, _req: &fuser::Request<'_>, ino: u64, flags: i32, reply: fuser::ReplyOpen) {
+        let write = flags & libc::O_ACCMODE != libc::O_RDONLY;
+        let (day, file) = match Node::from_ino(ino) {
+            Some(node) if !self.node_exists(node) => {
+                reply.error(libc::ENOENT);
                 return;
             }
+            Some(Node::DayFile(day, file)) => (day, file),
+            Some(Node::YearFile(year, file)) => {
+                if write {
+                    reply.error(libc::EROFS);
+                    return;
+                }
 
-            let offset2 = if offset >= 2 {
-                (offset - 2) as usize
-            } else {
-                0
-            };
+                match self.open_year_file(year, file).and_then(load) {
+                    Ok(data) => {
+                        let fh = self.insert_handle(Handle::Data(data));
+                        reply.opened(fh, 0);
+                    }
+                    Err(err) => reply.error(err),
+                }
+                return;
+            }
+            Some(Node::LeaderboardJson(board, year)) => {
+                if write {
+                    reply.error(libc::EROFS);
+                    return;
+                }
+
+                match self.open_leaderboard(board, year).and_then(load) {
+                    Ok(data) => {
+                        let fh = self.insert_handle(Handle::Data(data));
+                        reply.opened(fh, 0);
+                    }
+                    Err(err) => reply.error(err),
+                }
+                return;
+            }
+            Some(Node::Status) => {
+                if write {
+                    reply.error(libc::EROFS);
+                    return;
+                }
 
-            for (i, year) in (AOC_FIRST_YEAR..=latest.year).enumerate().skip(offset2) {
-                let date = DayAndYear::new(year, 0);
-                if reply.add(
-                    date.to_ino(),
-                    (i + 3) as i64,
-                    fuser::FileType::Directory,
-                    format!("{year}").as_str(),
-                ) {
-                    reply.ok();
+                let fh = self.insert_handle(Handle::Data(self.status_text().into_bytes().into()));
+                reply.opened(fh, fuser::consts::FOPEN_DIRECT_IO);
+                return;
+            }
+            Some(Node::NextUnlock) => {
+                if write {
+                    reply.error(libc::EROFS);
                     return;
                 }
+
+                let fh =
+                    self.insert_handle(Handle::Data(self.next_unlock_text().into_bytes().into()));
+                reply.opened(fh, fuser::consts::FOPEN_DIRECT_IO);
+                return;
             }
+            Some(Node::LeaderboardTable(board, year)) => {
+                if write {
+                    reply.error(libc::EROFS);
+                    return;
+                }
 
-            if offset <= (latest.year - AOC_FIRST_YEAR + 3) as i64 {
-                let _ = reply.add(
-                    LATEST_ROOT_INO,
-                    ((latest.year - AOC_FIRST_YEAR) + 4) as i64,
-                    fuser::FileType::Symlink,
-                    "latest",
-                );
+                match self.render_leaderboard(board, year) {
+                    Ok(table) => {
+                        let fh = self.insert_handle(Handle::Data(table.into_bytes().into()));
+                        reply.opened(fh, fuser::consts::FOPEN_DIRECT_IO);
+                    }
+                    Err(err) => reply.error(err),
+                }
+                return;
+            }
+            Some(
+                Node::Root
+                | Node::Year(_)
+                | Node::DayDir(_)
+                | Node::Leaderboards
+                | Node::Leaderboard(_),
+            ) => {
+                reply.error(libc::EISDIR);
+                return;
+            }
+            Some(Node::LatestYear | Node::LatestDay(_) | Node::Next) => {
+                reply.error(libc::EINVAL);
+                return;
+            }
+            None => {
+                reply.error(libc::ENOENT);
+                return;
             }
+        };
+
+        if let DayFile::Part(part) = file {
+            let handle = if write {
+                Handle::Answer {
+                    day,
+                    part,
+                    answer: Vec::new(),
+                    submitted: true,
+                }
+            } else {
+                Handle::Data(self.read_verdict(day, part).into())
+            };
 
-            reply.ok();
+            let fh = self.insert_handle(handle);
+            reply.opened(fh, fuser::consts::FOPEN_DIRECT_IO);
             return;
         }
 
-        let year = (ino / 100) as u32;
-        if ino % 100 != 0 {
-            reply.error(libc::ENOTDIR);
+        if write {
+            reply.error(libc::EROFS);
             return;
         }
 
-        if year < AOC_FIRST_YEAR || year > latest.year {
-            reply.error(libc::ENOENT);
+        if self.pending_day() == Some(day) {
+            self.open_when_unlocked(day, file, reply);
             return;
         }
 
-        if offset == 0 && reply.add(ino, 1, fuser::FileType::Directory, ".") {
-            reply.ok();
-            return;
+        match self.day_file_contents(day, file) {
+            Ok(data) => {
+                let fh = self.insert_handle(Handle::Data(data));
+                reply.opened(fh, 0);
+            }
+            Err(err) => {
+                reply.error(err);
+            }
         }
+    }
 
-        if offset <= 1 && reply.add(fuser::FUSE_ROOT_ID, 2, fuser::FileType::Directory, "..") {
-            reply.ok();
+    fn setattr(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        mode: Option<u32>,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        size: Option<u64>,
+        atime: Option<fuser::TimeOrNow>,
+        mtime: Option<fuser::TimeOrNow>,
+        _ctime: Option<SystemTime>,
+        fh: Option<u64>,
+        _crtime: Option<SystemTime>,
+        _chgtime: Option<SystemTime>,
+        _bkuptime: Option<SystemTime>,
+        _flags: Option<u32>,
+        reply: fuser::ReplyAttr,
+    ) {
+        log::trace!("setattr(..., ino={ino}, size={size:?}, fh={fh:?})");
+
+        // only the answer files can be truncated (`echo 42 > day01.part1`) or touched, and nothing
+        // else is changed on them
+        let changed = mode.is_some()
+            || uid.is_some()
+            || gid.is_some()
+            || size.is_some()
+            || atime.is_some()
+            || mtime.is_some();
+        if changed
+            && !matches!(
+                Node::from_ino(ino),
+                Some(Node::DayFile(_, DayFile::Part(_)))
+            )
+        {
+            reply.error(libc::EROFS);
             return;
         }
 
-        let offset2 = if offset >= 2 {
-            (offset - 2) as usize
-        } else {
-            0
-        };
-
-        let max_day = if year == latest.year {
-            latest.day
-        } else {
-            if year < AOC_FIRST_YEAR_WITH_12_DAYS {
-                25
-            } else {
-                12
-            }
-        };
-
-        for i in (1..=max_day).skip(offset2) {
-            if reply.add(
-                DayAndYear::new(year, i).to_ino(),
-                (i + 2) as i64,
-                fuser::FileType::RegularFile,
-                format!("day{i:02}.txt").as_str(),
-            ) {
-                reply.ok();
-                return;
+        if let Some(size) = size {
+            let mut handles = self.handles();
+            if let Some(Handle::Answer { answer, .. }) = fh.and_then(|fh| handles.get_mut(&fh)) {
+                answer.truncate(size as usize);
             }
         }
 
-        if offset <= max_day as i64 + 3 {
-            let _ = reply.add(
-                DayAndYear::new(year, 26).to_ino(),
-                max_day as i64 + 4,
-                fuser::FileType::Symlink,
-                "latest",
-            );
+        match self.getattr_impl(ino) {
+            Ok((ttl, attr)) => reply.attr(&ttl, &attr),
+            Err(err) => reply.error(err),
         }
+    }
 
-        reply.ok();
+    // the tree is generated, nothing can be added, removed or renamed
+    fn mknod(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _rdev: u32,
+        reply: fuser::ReplyEntry,
+    ) {
+        log::trace!("mknod(..., parent={parent}, name={name:?})");
+        reply.error(libc::EROFS);
     }
 
-    fn open(&mut self, _req: &fuser::Request<'_>, ino: u64, flags: i32, reply: fuser::ReplyOpen) {
-        #[allow(clippy::bad_bit_mask)]
-        if flags & libc::O_RDONLY != libc::O_RDONLY {
-            reply.error(libc::EROFS);
-            return;
-        }
+    fn mkdir(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        reply: fuser::ReplyEntry,
+    ) {
+        log::trace!("mkdir(..., parent={parent}, name={name:?})");
+        reply.error(libc::EROFS);
+    }
 
-        if ino == fuser::FUSE_ROOT_ID {
-            reply.error(libc::EISDIR);
-            return;
-        } else if ino == LATEST_ROOT_INO {
-            reply.error(libc::EINVAL);
+    fn unlink(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        reply: fuser::ReplyEmpty,
+    ) {
+        log::trace!("unlink(..., parent={parent}, name={name:?})");
+        reply.error(libc::EROFS);
+    }
+
+    fn rmdir(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        reply: fuser::ReplyEmpty,
+    ) {
+        log::trace!("rmdir(..., parent={parent}, name={name:?})");
+        reply.error(libc::EROFS);
+    }
+
+    fn symlink(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        link_name: &OsStr,
+        _target: &Path,
+        reply: fuser::ReplyEntry,
+    ) {
+        log::trace!("symlink(..., parent={parent}, link_name={link_name:?})");
+        reply.error(libc::EROFS);
+    }
+
+    fn rename(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        newparent: u64,
+        newname: &OsStr,
+        _flags: u32,
+        reply: fuser::ReplyEmpty,
+    ) {
+        log::trace!(
+            "rename(..., parent={parent}, name={name:?}, newparent={newparent}, newname={newname:?})"
+        );
+        reply.error(libc::EROFS);
+    }
+
+    fn link(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        newparent: u64,
+        newname: &OsStr,
+        reply: fuser::ReplyEntry,
+    ) {
+        log::trace!("link(..., ino={ino}, newparent={newparent}, newname={newname:?})");
+        reply.error(libc::EROFS);
+    }
+
+    fn create(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _flags: i32,
+        reply: fuser::ReplyCreate,
+    ) {
+        log::trace!("create(..., parent={parent}, name={name:?})");
+        reply.error(libc::EROFS);
+    }
+
+    fn write(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        fh: u64,
+        offset: i64,
+        data: &[u8],
+        _write_flags: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: fuser::ReplyWrite,
+    ) {
+        log::trace!(
+            "write(..., ino={ino}, offset={offset}, size={})",
+            data.len()
+        );
+
+        let mut handles = self.handles();
+        let Some(Handle::Answer {
+            answer, submitted, ..
+        }) = handles.get_mut(&fh)
+        else {
+            reply.error(libc::EBADF);
             return;
-        }
+        };
 
-        let latest = DayAndYear::last_unlocked_puzzle();
-        let day = DayAndYear::from_ino(ino);
-        if day.year < AOC_FIRST_YEAR || day.year > latest.year {
-            reply.error(libc::ENOENT);
+        let offset = usize::try_from(offset).unwrap_or(0);
+        let end = offset + data.len();
+        if end > MAX_ANSWER_LEN {
+            reply.error(libc::EFBIG);
             return;
         }
 
-        if day.day == 0 {
-            reply.error(libc::EISDIR);
-            return;
-        } else if day.day == 26 {
-            reply.error(libc::EINVAL);
-            return;
-        } else if day.day
-            > if day.year == latest.year {
-                latest.day
-            } else {
-                if day.year < AOC_FIRST_YEAR_WITH_12_DAYS {
-                    25
-                } else {
-                    12
-                }
-            }
-        {
-            reply.error(libc::ENOENT);
-            return;
+        if answer.len() < end {
+            answer.resize(end, 0);
         }
 
-        match self.open_day_input(day) {
-            Ok(fd) => {
-                let raw_fd = fd.into_raw_fd();
-                reply.opened(raw_fd as u64, 0);
+        answer[offset..end].copy_from_slice(data);
+        *submitted = false;
+        reply.written(data.len() as u32);
+    }
+
+    fn flush(
+        &mut self,
+        _req: &fuser::Request<'_>,
+        ino: u64,
+        fh: u64,
+        _lock_owner: u64,
+        reply: fuser::ReplyEmpty,
+    ) {
+        log::trace!("flush(..., ino={ino}, fh={fh})");
+
+        let (day, part, answer) = match self.handles().get(&fh) {
+            Some(Handle::Answer {
+                day,
+                part,
+                answer,
+                submitted: false,
+            }) => (
+                *day,
+                *part,
+                String::from_utf8_lossy(answer).trim().to_owned(),
+            ),
+            _ => {
+                reply.ok();
+                return;
             }
-            Err(err) => {
+        };
+
+        if !answer.is_empty() {
+            if let Err(err) = self.submit_answer(day, part, &answer) {
                 reply.error(err);
+                return;
             }
         }
+
+        if let Some(Handle::Answer { submitted, .. }) = self.handles().get_mut(&fh) {
+            *submitted = true;
+        }
+
+        reply.ok();
     }
 
     fn release(
@@ -612,10 +1873,8 @@ impl fuser::Filesystem for AoCFilesystem {
         _flush: bool,
         reply: fuser::ReplyEmpty,
     ) {
-        let day = DayAndYear::from_ino(ino);
-        log::trace!("release(close) \"{}/day{:02}.txt\"", day.year, day.day);
-        let fd = unsafe { File::from_raw_fd(fh as i32) };
-        drop(fd);
+        log::trace!("release(close) ino={ino}");
+        self.handles().remove(&fh);
 
         reply.ok();
     }
@@ -631,29 +1890,78 @@ impl fuser::Filesystem for AoCFilesystem {
         _lock_owner: Option<u64>,
         reply: fuser::ReplyData,
     ) {
-        let day = DayAndYear::from_ino(ino);
-        log::trace!(
-            "read(\"{}/day{:02}.txt\", offset={offset}, size={size})",
-            day.year,
-            day.day
-        );
-
-        let fd = ManuallyDrop::new(unsafe { File::from_raw_fd(fh as i32) });
-        let size = fd
-            .metadata()
-            .expect("File::metadata()")
-            .size()
-            .min(size as u64) as usize;
-
-        let mut buff = vec![0; size];
-        match fd.read_exact_at(&mut buff, offset.try_into().unwrap_or(0)) {
-            Ok(()) => (),
-            Err(err) => {
-                reply.error(err.raw_os_error().unwrap_or(libc::EINVAL));
+        log::trace!("read(..., ino={ino}, offset={offset}, size={size})");
+
+        let handles = self.handles();
+        let data: &[u8] = match handles.get(&fh) {
+            Some(Handle::Data(data)) => data,
+            Some(Handle::Answer { answer, .. }) => answer,
+            None => {
+                reply.error(libc::EBADF);
                 return;
             }
+        };
+
+        match slice_at(data, offset, size) {
+            Some(chunk) => reply.data(chunk),
+            None => reply.error(libc::EINVAL),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slice_at;
 
-        reply.data(&buff);
+    const DATA: &[u8] = b"0123456789";
+
+    #[test]
+    fn read_within_data() {
+        assert_eq!(slice_at(DATA, 0, 4), Some(&b"0123"[..]));
+        assert_eq!(slice_at(DATA, 3, 4), Some(&b"3456"[..]));
+        assert_eq!(slice_at(DATA, 0, 10), Some(DATA));
+    }
+
+    #[test]
+    fn short_read_at_eof() {
+        assert_eq!(slice_at(DATA, 8, 4096), Some(&b"89"[..]));
+        assert_eq!(slice_at(DATA, 0, 4096), Some(DATA));
+    }
+
+    #[test]
+    fn empty_read_past_eof() {
+        assert_eq!(slice_at(DATA, 10, 4), Some(&b""[..]));
+        assert_eq!(slice_at(DATA, 4096, 4096), Some(&b""[..]));
+        assert_eq!(slice_at(DATA, i64::MAX, u32::MAX), Some(&b""[..]));
+        assert_eq!(slice_at(b"", 0, 4096), Some(&b""[..]));
+    }
+
+    #[test]
+    fn zero_sized_read() {
+        assert_eq!(slice_at(DATA, 5, 0), Some(&b""[..]));
+    }
+
+    #[test]
+    fn negative_offset() {
+        assert_eq!(slice_at(DATA, -1, 4), None);
+    }
+
+    #[test]
+    fn chunked_reads_rebuild_the_data() {
+        for chunk in 1..=11 {
+            let mut read = Vec::new();
+            let mut offset = 0;
+            loop {
+                let data = slice_at(DATA, offset, chunk).expect("positive offset");
+                if data.is_empty() {
+                    break;
+                }
+
+                read.extend_from_slice(data);
+                offset += data.len() as i64;
+            }
+
+            assert_eq!(read, DATA, "chunks of {chunk} bytes");
+        }
     }
 }
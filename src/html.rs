@@ -0,0 +1,308 @@
+// Minimal HTML tree builder, good enough for the pages served by adventofcode.com
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+impl Element {
+    fn new(name: String, attrs: Vec<(String, String)>) -> Self {
+        Self {
+            name,
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.attr("class")
+            .is_some_and(|classes| classes.split_ascii_whitespace().any(|c| c == class))
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(el) => Some(el),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.push_text(&mut text);
+        text
+    }
+
+    fn push_text(&self, text: &mut String) {
+        for child in &self.children {
+            match child {
+                Node::Element(el) => el.push_text(text),
+                Node::Text(t) => text.push_str(t),
+            }
+        }
+    }
+
+    // all descendants matching `pred`, in document order (matches are not searched further)
+    pub fn find_all<P: Fn(&Element) -> bool>(&self, pred: P) -> Vec<&Element> {
+        let mut found = Vec::new();
+        self.find_all_impl(&pred, &mut found);
+        found
+    }
+
+    fn find_all_impl<'a, P: Fn(&Element) -> bool>(
+        &'a self,
+        pred: &P,
+        found: &mut Vec<&'a Element>,
+    ) {
+        for el in self.elements() {
+            if pred(el) {
+                found.push(el);
+            } else {
+                el.find_all_impl(pred, found);
+            }
+        }
+    }
+}
+
+pub fn parse(html: &str) -> Element {
+    let mut stack = vec![Element::new("#document".into(), Vec::new())];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').unwrap_or(after.len());
+            let name = after[..end].trim().to_ascii_lowercase();
+            rest = after.get(end + 1..).unwrap_or("");
+            close_element(&mut stack, &name);
+        } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            let (element, self_closing, after) = parse_start_tag(&rest[1..]);
+            rest = after;
+
+            if RAW_TEXT_ELEMENTS.contains(&element.name.as_str()) {
+                let closing = format!("</{}", element.name);
+                rest = find_ignore_case(rest, &closing).map_or("", |end| &rest[end..]);
+                push_child(&mut stack, Node::Element(element));
+            } else if self_closing || VOID_ELEMENTS.contains(&element.name.as_str()) {
+                push_child(&mut stack, Node::Element(element));
+            } else {
+                stack.push(element);
+            }
+        } else {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| c == '<')
+                .map_or(rest.len(), |(end, _)| end);
+            push_child(&mut stack, Node::Text(decode_entities(&rest[..end])));
+            rest = &rest[end..];
+        }
+    }
+
+    while stack.len() > 1 {
+        let el = stack.pop().expect("stack.len() > 1");
+        push_child(&mut stack, Node::Element(el));
+    }
+
+    stack.pop().expect("document element")
+}
+
+fn push_child(stack: &mut [Element], node: Node) {
+    stack
+        .last_mut()
+        .expect("document element is never popped")
+        .children
+        .push(node);
+}
+
+fn close_element(stack: &mut Vec<Element>, name: &str) {
+    // stray closing tags are ignored
+    let Some(pos) = stack.iter().skip(1).rposition(|el| el.name == name) else {
+        return;
+    };
+
+    while stack.len() > pos + 1 {
+        let el = stack.pop().expect("stack.len() > pos + 1");
+        push_child(stack, Node::Element(el));
+    }
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+// `tag` starts right after the `<`, returns the element, whether it was self-closing and the rest of the input
+fn parse_start_tag(tag: &str) -> (Element, bool, &str) {
+    let name_end = tag
+        .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len());
+    let name = tag[..name_end].to_ascii_lowercase();
+    let mut rest = &tag[name_end..];
+    let mut attrs = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (Element::new(name, attrs), true, after);
+        } else if let Some(after) = rest.strip_prefix('>') {
+            return (Element::new(name, attrs), false, after);
+        } else if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            continue;
+        } else if rest.is_empty() {
+            return (Element::new(name, attrs), false, rest);
+        }
+
+        let attr_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let attr = rest[..attr_end].to_ascii_lowercase();
+        rest = rest[attr_end..].trim_start();
+
+        let value = if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (value, after) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = after[1..].find(quote).map_or(after.len(), |end| end + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+
+            rest = after;
+            decode_entities(value)
+        } else {
+            String::new()
+        };
+
+        attrs.push((attr, value));
+    }
+}
+
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_entities, parse};
+
+    #[test]
+    fn elements_and_attributes() {
+        let document = parse(
+            "<!DOCTYPE html>\n<main><article class=\"day-desc\"><h2 id=part2>--- Part Two ---</h2>\
+             <p>Hi <em class='star'>there</em></p></article></main>",
+        );
+        let articles = document.find_all(|el| el.name == "article");
+        assert_eq!(articles.len(), 1);
+        assert!(articles[0].has_class("day-desc"));
+
+        let h2 = articles[0].elements().next().unwrap();
+        assert_eq!(h2.attr("id"), Some("part2"));
+        assert_eq!(articles[0].text(), "--- Part Two ---Hi there");
+    }
+
+    #[test]
+    fn void_unclosed_and_stray_tags() {
+        let document = parse("<div><p>one<br>two</span></div><p>three");
+        let paragraphs = document.find_all(|el| el.name == "p");
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text(), "onetwo");
+        assert_eq!(paragraphs[0].find_all(|el| el.name == "br").len(), 1);
+        assert_eq!(paragraphs[1].text(), "three");
+    }
+
+    #[test]
+    fn comments_and_scripts() {
+        let document = parse(
+            "<!-- <p>hidden</p> --><script>if (a < b) { x = \"</p>\"; }</script><p>shown</p>",
+        );
+        assert_eq!(document.find_all(|el| el.name == "p").len(), 1);
+        assert_eq!(document.text(), "shown");
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            decode_entities("&lt;x&gt; &amp;&amp; &quot;y&quot; &#39;&#x41; & &bogus;"),
+            "<x> && \"y\" 'A & &bogus;"
+        );
+    }
+}
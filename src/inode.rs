@@ -0,0 +1,329 @@
+use crate::{calendar::MAX_DAYS, config::Layout, filesystem::DayAndYear};
+
+// AoC started in 2015, so year 2000 day 0 can be used as a marker for the `latest` symlink at fs root
+const LATEST_ROOT_INO: u64 = DayAndYear::new(2000, 0).to_ino();
+const LEADERBOARDS_INO: u64 = DayAndYear::new(2000, 1).to_ino();
+const STATUS_INO: u64 = DayAndYear::new(2000, 2).to_ino();
+const NEXT_INO: u64 = DayAndYear::new(2000, 3).to_ino();
+const NEXT_UNLOCK_INO: u64 = DayAndYear::new(2000, 4).to_ino();
+// the nodes of a day share the inode of its input, offset by a multiple of this
+const INO_KIND_STRIDE: u64 = 1_000_000;
+const DAY_DIR_KIND: u64 = 2;
+const EXAMPLE_KIND_OFFSET: u64 = 10;
+pub const MAX_EXAMPLES: u8 = 89;
+// kind of the nodes of the n-th configured private leaderboard
+const LEADERBOARD_KIND_OFFSET: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayFile {
+    Input,
+    Puzzle,
+    Example(u8),
+    // answer submission, part 1 or 2
+    Part(u8),
+    // accepted answer of part 1 or 2
+    Answer(u8),
+}
+
+impl DayFile {
+    const fn from_kind(kind: u64) -> Option<DayFile> {
+        match kind {
+            0 => Some(DayFile::Input),
+            1 => Some(DayFile::Puzzle),
+            3 => Some(DayFile::Part(1)),
+            4 => Some(DayFile::Part(2)),
+            5 => Some(DayFile::Answer(1)),
+            6 => Some(DayFile::Answer(2)),
+            EXAMPLE_KIND_OFFSET.. => {
+                let example = kind - EXAMPLE_KIND_OFFSET;
+                if example >= 1 && example <= MAX_EXAMPLES as u64 {
+                    Some(DayFile::Example(example as u8))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    const fn kind(self) -> u64 {
+        match self {
+            DayFile::Input => 0,
+            DayFile::Puzzle => 1,
+            DayFile::Example(example) => EXAMPLE_KIND_OFFSET + example as u64,
+            DayFile::Part(part) => 2 + part as u64,
+            DayFile::Answer(part) => 4 + part as u64,
+        }
+    }
+
+    pub fn file_name(self, day: u8, layout: Layout) -> String {
+        match (layout, self) {
+            (Layout::Flat, DayFile::Input) => format!("day{day:02}.txt"),
+            (Layout::Flat, DayFile::Puzzle) => format!("day{day:02}.md"),
+            (Layout::Flat, DayFile::Example(example)) => {
+                format!("day{day:02}.example{example}.txt")
+            }
+            (Layout::Flat, DayFile::Part(part)) => format!("day{day:02}.part{part}"),
+            (Layout::Flat, DayFile::Answer(part)) => format!("day{day:02}.answer{part}"),
+            (Layout::PerDay, DayFile::Input) => "input.txt".into(),
+            (Layout::PerDay, DayFile::Puzzle) => "puzzle.md".into(),
+            (Layout::PerDay, DayFile::Example(example)) => format!("example{example}.txt"),
+            (Layout::PerDay, DayFile::Part(part)) => format!("part{part}"),
+            (Layout::PerDay, DayFile::Answer(part)) => format!("answer{part}"),
+        }
+    }
+}
+
+// files of `/YYYY` that aren't about a single day, they use the input kind with a day past 26
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YearFile {
+    Stats,
+    StatsJson,
+    Stars,
+    StarsJson,
+}
+
+impl YearFile {
+    pub const ALL: [YearFile; 4] = [
+        YearFile::Stats,
+        YearFile::StatsJson,
+        YearFile::Stars,
+        YearFile::StarsJson,
+    ];
+
+    const fn from_day(day: u8) -> Option<YearFile> {
+        match day {
+            27 => Some(YearFile::Stats),
+            28 => Some(YearFile::StatsJson),
+            29 => Some(YearFile::Stars),
+            30 => Some(YearFile::StarsJson),
+            _ => None,
+        }
+    }
+
+    const fn day(self) -> u8 {
+        match self {
+            YearFile::Stats => 27,
+            YearFile::StatsJson => 28,
+            YearFile::Stars => 29,
+            YearFile::StarsJson => 30,
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            YearFile::Stats => "stats.txt",
+            YearFile::StatsJson => "stats.json",
+            YearFile::Stars => "stars",
+            YearFile::StarsJson => "stars.json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    Root,
+    // `/latest`
+    LatestYear,
+    // `/YYYY`
+    Year(u32),
+    // `/YYYY/latest`
+    LatestDay(u32),
+    // `/YYYY/dayNN`, only with the per-day layout
+    DayDir(DayAndYear),
+    DayFile(DayAndYear, DayFile),
+    YearFile(u32, YearFile),
+    // `/.status`
+    Status,
+    // `/next`, symlink to the next puzzle to unlock
+    Next,
+    // `/next_unlock`
+    NextUnlock,
+    // `/leaderboards`
+    Leaderboards,
+    // `/leaderboards/<id>`, identified by its index in the configuration
+    Leaderboard(u32),
+    // `/leaderboards/<id>/YYYY.json`
+    LeaderboardJson(u32, u32),
+    // `/leaderboards/<id>/YYYY.txt`, rendered from the JSON
+    LeaderboardTable(u32, u32),
+}
+
+impl Node {
+    pub const fn from_ino(ino: u64) -> Option<Node> {
+        if ino == fuser::FUSE_ROOT_ID {
+            return Some(Node::Root);
+        } else if ino == LATEST_ROOT_INO {
+            return Some(Node::LatestYear);
+        } else if ino == LEADERBOARDS_INO {
+            return Some(Node::Leaderboards);
+        } else if ino == STATUS_INO {
+            return Some(Node::Status);
+        } else if ino == NEXT_INO {
+            return Some(Node::Next);
+        } else if ino == NEXT_UNLOCK_INO {
+            return Some(Node::NextUnlock);
+        }
+
+        let kind = ino / INO_KIND_STRIDE;
+        let day = DayAndYear::from_ino(ino % INO_KIND_STRIDE);
+        if kind >= LEADERBOARD_KIND_OFFSET {
+            let board = (kind - LEADERBOARD_KIND_OFFSET) as u32;
+            return match (day.year, day.day) {
+                (2000, 0) => Some(Node::Leaderboard(board)),
+                (year, 0) => Some(Node::LeaderboardJson(board, year)),
+                (year, 1) => Some(Node::LeaderboardTable(board, year)),
+                _ => None,
+            };
+        }
+
+        if day.day < 1 || day.day > MAX_DAYS {
+            return match (kind, day.day) {
+                (0, 0) => Some(Node::Year(day.year)),
+                (0, 26) => Some(Node::LatestDay(day.year)),
+                (0, _) => match YearFile::from_day(day.day) {
+                    Some(file) => Some(Node::YearFile(day.year, file)),
+                    None => None,
+                },
+                _ => None,
+            };
+        }
+
+        if kind == DAY_DIR_KIND {
+            return Some(Node::DayDir(day));
+        }
+
+        match DayFile::from_kind(kind) {
+            Some(file) => Some(Node::DayFile(day, file)),
+            None => None,
+        }
+    }
+
+    pub const fn ino(self) -> u64 {
+        match self {
+            Node::Root => fuser::FUSE_ROOT_ID,
+            Node::LatestYear => LATEST_ROOT_INO,
+            Node::Year(year) => DayAndYear::new(year, 0).to_ino(),
+            Node::LatestDay(year) => DayAndYear::new(year, 26).to_ino(),
+            Node::DayDir(day) => DAY_DIR_KIND * INO_KIND_STRIDE + day.to_ino(),
+            Node::DayFile(day, file) => file.kind() * INO_KIND_STRIDE + day.to_ino(),
+            Node::YearFile(year, file) => DayAndYear::new(year, file.day()).to_ino(),
+            Node::Status => STATUS_INO,
+            Node::Next => NEXT_INO,
+            Node::NextUnlock => NEXT_UNLOCK_INO,
+            Node::Leaderboards => LEADERBOARDS_INO,
+            Node::Leaderboard(board) => {
+                (LEADERBOARD_KIND_OFFSET + board as u64) * INO_KIND_STRIDE
+                    + DayAndYear::new(2000, 0).to_ino()
+            }
+            Node::LeaderboardJson(board, year) => {
+                (LEADERBOARD_KIND_OFFSET + board as u64) * INO_KIND_STRIDE
+                    + DayAndYear::new(year, 0).to_ino()
+            }
+            Node::LeaderboardTable(board, year) => {
+                (LEADERBOARD_KIND_OFFSET + board as u64) * INO_KIND_STRIDE
+                    + DayAndYear::new(year, 1).to_ino()
+            }
+        }
+    }
+
+    pub const fn file_type(self) -> fuser::FileType {
+        match self {
+            Node::Root
+            | Node::Year(_)
+            | Node::DayDir(_)
+            | Node::Leaderboards
+            | Node::Leaderboard(_) => fuser::FileType::Directory,
+            Node::LatestYear | Node::LatestDay(_) | Node::Next => fuser::FileType::Symlink,
+            Node::DayFile(..)
+            | Node::YearFile(..)
+            | Node::Status
+            | Node::NextUnlock
+            | Node::LeaderboardJson(..)
+            | Node::LeaderboardTable(..) => fuser::FileType::RegularFile,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DayFile, Node, YearFile, EXAMPLE_KIND_OFFSET, INO_KIND_STRIDE, LEADERBOARD_KIND_OFFSET,
+        MAX_EXAMPLES,
+    };
+    use crate::{
+        calendar::{FIRST_YEAR, LAST_YEAR, MAX_DAYS},
+        filesystem::DayAndYear,
+    };
+
+    fn all_nodes() -> Vec<Node> {
+        let mut nodes = vec![
+            Node::Root,
+            Node::LatestYear,
+            Node::Status,
+            Node::Next,
+            Node::NextUnlock,
+            Node::Leaderboards,
+        ];
+        for year in [FIRST_YEAR, 2024, LAST_YEAR] {
+            nodes.extend([Node::Year(year), Node::LatestDay(year)]);
+            nodes.extend(YearFile::ALL.map(|file| Node::YearFile(year, file)));
+            for day in [1, 12, MAX_DAYS] {
+                let day = DayAndYear::new(year, day);
+                nodes.push(Node::DayDir(day));
+                for file in [
+                    DayFile::Input,
+                    DayFile::Puzzle,
+                    DayFile::Example(1),
+                    DayFile::Example(MAX_EXAMPLES),
+                    DayFile::Part(1),
+                    DayFile::Part(2),
+                    DayFile::Answer(1),
+                    DayFile::Answer(2),
+                ] {
+                    nodes.push(Node::DayFile(day, file));
+                }
+            }
+
+            for board in [0, 1, 41] {
+                nodes.extend([
+                    Node::LeaderboardJson(board, year),
+                    Node::LeaderboardTable(board, year),
+                ]);
+            }
+        }
+
+        nodes.extend([0, 1, 41].map(Node::Leaderboard));
+        nodes
+    }
+
+    #[test]
+    fn round_trip() {
+        for node in all_nodes() {
+            assert_eq!(Node::from_ino(node.ino()), Some(node), "ino {}", node.ino());
+        }
+    }
+
+    #[test]
+    fn unique_inodes() {
+        let mut inodes: Vec<u64> = all_nodes().into_iter().map(Node::ino).collect();
+        inodes.sort_unstable();
+        let len = inodes.len();
+        inodes.dedup();
+        assert_eq!(inodes.len(), len);
+    }
+
+    #[test]
+    fn invalid_inodes() {
+        let day = DayAndYear::new(2024, 1);
+        let past_last_day = DayAndYear::new(2024, MAX_DAYS + 1);
+        for ino in [
+            DayFile::Puzzle.kind() * INO_KIND_STRIDE + past_last_day.to_ino(),
+            (EXAMPLE_KIND_OFFSET - 1) * INO_KIND_STRIDE + day.to_ino(),
+            DayAndYear::new(2024, 31).to_ino(),
+            LEADERBOARD_KIND_OFFSET * INO_KIND_STRIDE + DayAndYear::new(2024, 2).to_ino(),
+        ] {
+            assert_eq!(Node::from_ino(ino), None, "ino {ino}");
+        }
+    }
+}
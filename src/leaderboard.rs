@@ -0,0 +1,154 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use chrono::DateTime;
+use serde::Deserialize;
+
+use crate::calendar::aoc_offset;
+
+#[derive(Debug, Deserialize)]
+struct Leaderboard {
+    members: HashMap<String, Member>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    stars: u32,
+    local_score: u64,
+    #[serde(default)]
+    completion_day_level: BTreeMap<u8, BTreeMap<u8, Star>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Star {
+    get_star_ts: i64,
+}
+
+impl Member {
+    fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+}
+
+// Renders the JSON of a private leaderboard as a table sorted by local score, followed by
+// the star timestamps (AoC time, UTC-5) of each member
+pub fn render(json: &[u8]) -> Result<String, serde_json::Error> {
+    let leaderboard: Leaderboard = serde_json::from_slice(json)?;
+    let mut members: Vec<&Member> = leaderboard.members.values().collect();
+    members.sort_by(|a, b| {
+        b.local_score
+            .cmp(&a.local_score)
+            .then_with(|| b.stars.cmp(&a.stars))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Rank  Score  Stars  Name");
+    for (rank, member) in members.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{:>4}  {:>5}  {:>5}  {}",
+            rank + 1,
+            member.local_score,
+            member.stars,
+            member.display_name()
+        );
+    }
+
+    for member in members
+        .iter()
+        .filter(|m| !m.completion_day_level.is_empty())
+    {
+        let _ = writeln!(out, "\n{}", member.display_name());
+        let _ = writeln!(out, "Day  Part 1          Part 2          Delta");
+        for (day, stars) in &member.completion_day_level {
+            let part1 = stars.get(&1).map(|star| star.get_star_ts);
+            let part2 = stars.get(&2).map(|star| star.get_star_ts);
+            let delta = match (part1, part2) {
+                (Some(part1), Some(part2)) => format_duration(part2 - part1),
+                _ => "-".into(),
+            };
+
+            let _ = writeln!(
+                out,
+                "{:>3}  {:<14}  {:<14}  {}",
+                day,
+                format_timestamp(part1),
+                format_timestamp(part2),
+                delta
+            );
+        }
+    }
+
+    Ok(out)
+}
+
+fn format_timestamp(timestamp: Option<i64>) -> String {
+    match timestamp.and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+        Some(time) => time
+            .with_timezone(&aoc_offset())
+            .format("%m-%d %H:%M:%S")
+            .to_string(),
+        None => "-".into(),
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+
+    // trimmed from https://adventofcode.com/2023/leaderboard/private/view/1.json
+    const JSON: &str = r#"{"event":"2023","owner_id":1,"members":{
+"2":{"id":2,"name":null,"stars":1,"local_score":10,"global_score":0,"last_star_ts":1701417600,
+  "completion_day_level":{"1":{"1":{"get_star_ts":1701417600,"star_index":1}}}},
+"3":{"id":3,"name":"carol","stars":0,"local_score":0,"global_score":0,"last_star_ts":0,
+  "completion_day_level":{}},
+"1":{"id":1,"name":"alice","stars":3,"local_score":20,"global_score":0,"last_star_ts":1701493500,
+  "completion_day_level":{
+    "2":{"1":{"get_star_ts":1701493500,"star_index":3}},
+    "1":{"1":{"get_star_ts":1701407554,"star_index":0},"2":{"get_star_ts":1701411296,"star_index":1}}}}
+}}"#;
+
+    #[test]
+    fn table_and_timestamps() {
+        let expected = "\
+Rank  Score  Stars  Name
+   1     20      3  alice
+   2     10      1  (anonymous user #2)
+   3      0      0  carol
+
+alice
+Day  Part 1          Part 2          Delta
+  1  12-01 00:12:34  12-01 01:14:56  1:02:22
+  2  12-02 00:05:00  -               -
+
+(anonymous user #2)
+Day  Part 1          Part 2          Delta
+  1  12-01 03:00:00  -               -
+";
+        assert_eq!(render(JSON.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(render(b"<!DOCTYPE html>").is_err());
+        assert!(render(br#"{"members":{"1":{"id":1}}}"#).is_err());
+    }
+}
@@ -1,13 +1,28 @@
-use std::{path::PathBuf, process::ExitCode};
+use std::{path::PathBuf, process::ExitCode, time::Duration};
 
+use chrono::{DateTime, Utc};
 use clap::{
     builder::styling::{AnsiColor, Color, Style},
-    Parser,
+    error::ErrorKind,
+    CommandFactory, Parser, Subcommand,
 };
-use fuser::{mount2, MountOption};
+use fuser::{MountOption, Session};
 
+mod archive;
+mod cache;
+mod calendar;
+mod clock;
 mod config;
 mod filesystem;
+mod html;
+mod inode;
+mod leaderboard;
+mod prefetch;
+mod puzzle;
+mod stars;
+mod stats;
+mod status;
+mod verify;
 mod web;
 
 fn get_styles() -> clap::builder::Styles {
@@ -45,14 +60,21 @@ fn get_styles() -> clap::builder::Styles {
 }
 
 #[derive(Debug, clap::Parser)]
-#[command(styles = get_styles())]
+#[command(styles = get_styles(), subcommand_negates_reqs = true)]
 struct CmdArgs {
-    #[arg(help = "Act as a client, and mount FUSE at given path")]
-    mount_point: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        required = true,
+        help = "Act as a client, and mount FUSE at given path"
+    )]
+    mount_point: Option<PathBuf>,
 
     #[arg(
         short,
         long,
+        global = true,
         help = "TOML file containing configuration",
         default_value = "aoc-fs-config.toml"
     )]
@@ -66,10 +88,157 @@ struct CmdArgs {
 
     #[arg(long, help = "Allow all users to access the filesystem")]
     allow_other: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_parser = clock::parse_date,
+        help = "Pretend the current date is this one (`2024-12-03`, `2024-12-03T23:59:50` in UTC-5, or RFC 3339)"
+    )]
+    pretend_date: Option<DateTime<Utc>>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "pretend_date",
+        help = "Stop the clock at the pretended date instead of letting it run"
+    )]
+    freeze_clock: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "Download every released input missing from the cache, without mounting")]
+    Prefetch {
+        #[arg(long, help = "Only download the inputs of this year")]
+        year: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Also download the puzzle pages (description, examples and answers)"
+        )]
+        all: bool,
+
+        #[arg(
+            long,
+            value_parser = parse_seconds,
+            default_value = "2",
+            help = "Seconds to wait between requests"
+        )]
+        delay: Duration,
+    },
+
+    #[command(about = "Manage the cache dir")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommand {
+    #[command(
+        about = "Look for empty or invalid inputs, files of puzzles that don't exist and unexpected names"
+    )]
+    Verify {
+        #[arg(
+            long,
+            help = "Move the bad files to the .quarantine directory and download the inputs again"
+        )]
+        repair: bool,
+    },
+
+    #[command(about = "Write the cache of the configured account to a .tar.zst archive")]
+    Export {
+        #[arg(help = "Archive to create")]
+        file: PathBuf,
+    },
+
+    #[command(
+        about = "Add the files of an exported archive to the cache, refusing conflicting inputs or answers"
+    )]
+    Import {
+        #[arg(help = "Archive written by `cache export`")]
+        file: PathBuf,
+    },
+}
+
+fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    match seconds.parse::<f64>().map(Duration::try_from_secs_f64) {
+        Ok(Ok(duration)) => Ok(duration),
+        _ => Err(format!("invalid number of seconds {:?}", seconds)),
+    }
+}
+
+fn cache_command(config: &config::Config, clock: clock::Clock, command: CacheCommand) -> ExitCode {
+    match command {
+        CacheCommand::Verify { repair } => {
+            let report = match verify::verify(config, clock, repair) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Failed to verify {:?}: {}", config.cache_dir(), e);
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            for problem in &report.problems {
+                println!("{}: {}", problem.path.display(), problem.reason);
+            }
+
+            println!(
+                "{} files checked, {} problems",
+                report.checked,
+                report.problems.len()
+            );
+            if repair {
+                println!(
+                    "{} quarantined, {} inputs downloaded again, {} failed",
+                    report.quarantined, report.redownloaded, report.failed
+                );
+            }
+
+            if report.problems.is_empty() || (repair && report.failed == 0) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        CacheCommand::Export { file } => match archive::export(config, &file) {
+            Ok(exported) => {
+                println!("{} files exported to {:?}", exported, file);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to export the cache to {:?}: {}", file, e);
+                ExitCode::FAILURE
+            }
+        },
+        CacheCommand::Import { file } => match archive::import(config, &file) {
+            Ok(summary) => {
+                println!(
+                    "{} files imported, {} already cached, {} local versions kept",
+                    summary.imported, summary.identical, summary.kept
+                );
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to import {:?}: {}", file, e);
+                ExitCode::FAILURE
+            }
+        },
+    }
 }
 
 fn main() -> ExitCode {
     let args = CmdArgs::parse();
+    if args.command.is_some() && args.mount_point.is_some() {
+        CmdArgs::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "a mount point can't be given with a subcommand",
+            )
+            .exit();
+    }
     pretty_env_logger::init();
 
     let mut mount_options = Vec::from_iter([
@@ -77,7 +246,6 @@ fn main() -> ExitCode {
         MountOption::NoSuid,
         MountOption::NoDev,
         MountOption::NoAtime,
-        MountOption::RO,
         MountOption::DefaultPermissions,
         MountOption::FSName("aoc-fs".into()),
     ]);
@@ -105,9 +273,51 @@ fn main() -> ExitCode {
         }
     };
 
-    let fs = filesystem::AoCFilesystem::new(config);
-    log::trace!("Mounting fs on {:?}", args.mount_point);
-    match mount2(fs, args.mount_point, &mount_options) {
+    let clock = match args.pretend_date {
+        Some(date) => clock::Clock::pretend(date, args.freeze_clock),
+        None => clock::Clock::System,
+    };
+
+    match args.command {
+        Some(Command::Prefetch { year, all, delay }) => {
+            return match prefetch::prefetch(&config, clock, year, all, delay) {
+                Ok(summary) => {
+                    println!(
+                        "{} downloaded, {} already cached, {} failed",
+                        summary.downloaded, summary.cached, summary.failed
+                    );
+                    if summary.failed == 0 {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::FAILURE
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Prefetch failed: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Some(Command::Cache { command }) => return cache_command(&config, clock, command),
+        None => (),
+    }
+
+    let mount_point = args
+        .mount_point
+        .expect("the mount point is required without subcommand");
+    let fs = filesystem::AoCFilesystem::new(config, clock);
+    let invalidator = fs.clone();
+    log::trace!("Mounting fs on {:?}", mount_point);
+    let mut session = match Session::new(fs, &mount_point, &mount_options) {
+        Ok(session) => session,
+        Err(e) => {
+            log::error!("mount error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    invalidator.start_invalidations(session.notifier());
+    match session.run() {
         Ok(()) => {
             log::info!("Filesystem unmounted");
             ExitCode::SUCCESS
@@ -0,0 +1,111 @@
+use std::{fs, io, path::Path, thread, time::Duration};
+
+use crate::{calendar, clock::Clock, config::Config, filesystem::DayAndYear, puzzle, web};
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub downloaded: usize,
+    pub cached: usize,
+    pub failed: usize,
+}
+
+// Downloads every released input of `year` (or of every year) missing from the cache, and the
+// puzzle pages too if `puzzles`, waiting `delay` between requests
+pub fn prefetch(
+    config: &Config,
+    clock: Clock,
+    year: Option<u32>,
+    puzzles: bool,
+    delay: Duration,
+) -> io::Result<Summary> {
+    let calendar = calendar::from_config(config);
+    let latest = calendar.last_unlocked_puzzle(clock.now());
+    let years: Vec<u32> = match year {
+        Some(year) if calendar.released_days(year, latest).is_some() => vec![year],
+        Some(year) => {
+            let message = format!("No puzzle of {} is released", year);
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        }
+        None => calendar.years(latest.year).collect(),
+    };
+
+    let mut fetcher = Fetcher::new(delay);
+
+    for year in years {
+        for day in 1..=calendar.released_days(year, latest).unwrap_or(0) {
+            let day = DayAndYear::new(year, day);
+            let input_path = config.cached_day_input(day);
+            fetcher.fetch(day, "input", &input_path, || {
+                web::download_input(
+                    day,
+                    &input_path,
+                    config.session_token(),
+                    config.username(),
+                    config.compress(),
+                )
+            });
+
+            if puzzles {
+                let puzzle_path = config.cached_day_puzzle(day);
+                fetcher.fetch(day, "puzzle", &puzzle_path, || {
+                    puzzle::download(config, day)
+                });
+            }
+        }
+    }
+
+    Ok(fetcher.summary)
+}
+
+pub struct Fetcher {
+    delay: Duration,
+    // whether a request was already sent, the delay is only waited between requests
+    requested: bool,
+    summary: Summary,
+}
+
+impl Fetcher {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            requested: false,
+            summary: Summary::default(),
+        }
+    }
+
+    pub fn summary(&self) -> &Summary {
+        &self.summary
+    }
+
+    // calls `download` unless `path` is already cached
+    pub fn fetch<F>(&mut self, day: DayAndYear, what: &str, path: &Path, download: F)
+    where
+        F: FnOnce() -> io::Result<()>,
+    {
+        if fs::metadata(path).is_ok_and(|metadata| metadata.len() > 0) {
+            self.summary.cached += 1;
+            return;
+        }
+
+        if self.requested {
+            thread::sleep(self.delay);
+        }
+
+        self.requested = true;
+        let downloaded = match path.parent() {
+            Some(parent) => fs::create_dir_all(parent).and_then(|()| download()),
+            None => download(),
+        };
+
+        match downloaded {
+            Ok(()) => {
+                println!("{}/day{:02}: {} downloaded", day.year, day.day, what);
+                self.summary.downloaded += 1;
+            }
+            Err(err) => {
+                eprintln!("{}/day{:02}: {} failed: {}", day.year, day.day, what, err);
+                self.summary.failed += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use std::{fs, io};
+
+use crate::{
+    cache,
+    config::Config,
+    filesystem::DayAndYear,
+    html::{self, Element, Node},
+    inode::MAX_EXAMPLES,
+    web::{self, BASE_URL},
+};
+
+// Downloads the puzzle page of `day` and caches its description and examples,
+// the description is written last so its presence means the rest is cached too
+pub fn download(config: &Config, day: DayAndYear) -> io::Result<()> {
+    let page = web::download_puzzle_page(day, config.session_token())?;
+    let markdown = match to_markdown(&page) {
+        Some(markdown) => markdown,
+        None => {
+            let message = format!(
+                "No puzzle description found for {}/day{:02}",
+                day.year, day.day
+            );
+            log::error!("{}", message);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+    };
+
+    let examples = examples(&page);
+    for (i, example) in examples.iter().enumerate() {
+        let path = config.cached_day_example(day, i as u8 + 1);
+        cache::write(&path, example.as_bytes(), config.compress())?;
+    }
+
+    // the page may have lost examples since it was last cached
+    for i in examples.len() + 1.. {
+        match fs::remove_file(config.cached_day_example(day, i as u8)) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    // answers are never removed, they stay available even if the page stops showing them
+    for (i, answer) in answers(&page).iter().enumerate().take(2) {
+        let path = config.cached_day_answer(day, i as u8 + 1);
+        cache::write(&path, format!("{answer}\n").as_bytes(), config.compress())?;
+    }
+
+    cache::write(
+        &config.cached_day_puzzle(day),
+        markdown.as_bytes(),
+        config.compress(),
+    )
+}
+
+// Most puzzles introduce their example input(s) with a paragraph mentioning "example"
+pub fn examples(page: &str) -> Vec<String> {
+    let document = html::parse(page);
+    let mut examples: Vec<String> = Vec::new();
+    for article in document.find_all(|el| el.name == "article" && el.has_class("day-desc")) {
+        let mut introduced = false;
+        for el in article.elements() {
+            if el.name != "pre" {
+                introduced = el.text().to_ascii_lowercase().contains("example");
+                continue;
+            }
+
+            let mut example = el.text();
+            if introduced && !example.trim().is_empty() {
+                if !example.ends_with('\n') {
+                    example.push('\n');
+                }
+
+                if !examples.contains(&example) && examples.len() < MAX_EXAMPLES as usize {
+                    examples.push(example);
+                }
+            }
+
+            introduced = false;
+        }
+    }
+
+    examples
+}
+
+// Converts the `<article class="day-desc">` blocks of a puzzle page to markdown,
+// returns `None` if the page does not contain any
+pub fn to_markdown(page: &str) -> Option<String> {
+    let document = html::parse(page);
+    let articles = document.find_all(|el| el.name == "article" && el.has_class("day-desc"));
+    if articles.is_empty() {
+        return None;
+    }
+
+    let mut markdown = String::new();
+    for article in articles {
+        render_blocks(article, &mut markdown);
+    }
+
+    let mut markdown = markdown.trim_end().to_owned();
+    markdown.push('\n');
+    Some(markdown)
+}
+
+// Answers of the solved parts, from the "Your puzzle answer was <code>...</code>." paragraphs
+pub fn answers(page: &str) -> Vec<String> {
+    let document = html::parse(page);
+    document
+        .find_all(|el| {
+            el.name == "p" && el.text().trim_start().starts_with("Your puzzle answer was")
+        })
+        .into_iter()
+        .filter_map(|p| p.elements().find(|el| el.name == "code"))
+        .map(|code| code.text().trim().to_owned())
+        .collect()
+}
+
+// Summarizes the page returned after submitting an answer, as
+// "<correct|too high|too low|incorrect|wait|wrong level|unknown>\n<message>\n"
+pub fn verdict(page: &str) -> String {
+    let document = html::parse(page);
+    let message = document
+        .find_all(|el| el.name == "article")
+        .first()
+        .map(|article| {
+            let mut message = String::new();
+            push_collapsed(&article.text(), &mut message);
+            message.trim().to_owned()
+        })
+        .unwrap_or_default();
+
+    let verdict = if message.starts_with("That's the right answer") {
+        "correct"
+    } else if message.starts_with("That's not the right answer") {
+        if message.contains("too high") {
+            "too high"
+        } else if message.contains("too low") {
+            "too low"
+        } else {
+            "incorrect"
+        }
+    } else if message.starts_with("You gave an answer too recently") {
+        "wait"
+    } else if message.starts_with("You don't seem to be solving the right level") {
+        "wrong level"
+    } else {
+        "unknown"
+    };
+
+    format!("{verdict}\n{message}\n")
+}
+
+fn render_blocks(el: &Element, out: &mut String) {
+    let mut inline = String::new();
+    for child in &el.children {
+        let el = match child {
+            Node::Element(el) if is_block(el) => el,
+            other => {
+                render_inline_node(other, &mut inline);
+                continue;
+            }
+        };
+
+        flush_paragraph(&mut inline, out);
+        match el.name.as_str() {
+            "h2" => {
+                let mut title = String::new();
+                render_inline(el, &mut title);
+                out.push_str("## ");
+                out.push_str(title.trim());
+                out.push_str("\n\n");
+            }
+            "pre" => {
+                let code = el.text();
+                let fence = if code.contains("```") { "~~~" } else { "```" };
+                out.push_str(fence);
+                out.push('\n');
+                out.push_str(&code);
+                if !code.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(fence);
+                out.push_str("\n\n");
+            }
+            "ul" | "ol" => {
+                for (i, item) in el.elements().filter(|e| e.name == "li").enumerate() {
+                    let mut text = String::new();
+                    render_inline(item, &mut text);
+                    if el.name == "ol" {
+                        out.push_str(&format!("{}. ", i + 1));
+                    } else {
+                        out.push_str("- ");
+                    }
+                    out.push_str(text.trim());
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+            _ => {
+                // "p" and anything else we don't know about
+                render_inline(el, &mut inline);
+                flush_paragraph(&mut inline, out);
+            }
+        }
+    }
+
+    flush_paragraph(&mut inline, out);
+}
+
+fn is_block(el: &Element) -> bool {
+    matches!(
+        el.name.as_str(),
+        "h1" | "h2" | "h3" | "p" | "pre" | "ul" | "ol" | "div" | "blockquote"
+    )
+}
+
+fn flush_paragraph(inline: &mut String, out: &mut String) {
+    let paragraph = inline.trim();
+    if !paragraph.is_empty() {
+        out.push_str(paragraph);
+        out.push_str("\n\n");
+    }
+
+    inline.clear();
+}
+
+fn render_inline(el: &Element, out: &mut String) {
+    for child in &el.children {
+        render_inline_node(child, out);
+    }
+}
+
+fn render_inline_node(node: &Node, out: &mut String) {
+    let el = match node {
+        Node::Text(text) => {
+            push_collapsed(text, out);
+            return;
+        }
+        Node::Element(el) => el,
+    };
+
+    match el.name.as_str() {
+        "em" | "strong" | "b" | "i" => {
+            out.push('*');
+            render_inline(el, out);
+            out.push('*');
+        }
+        "code" => {
+            // AoC highlights important values with `<code><em>...</em></code>`
+            let emphasized = matches!(el.children.as_slice(), [Node::Element(e)] if e.name == "em");
+            if emphasized {
+                out.push_str("**");
+            }
+            push_code_span(&el.text(), out);
+            if emphasized {
+                out.push_str("**");
+            }
+        }
+        "a" => {
+            let mut text = String::new();
+            render_inline(el, &mut text);
+            match el.attr("href") {
+                Some(href) => {
+                    out.push('[');
+                    out.push_str(text.trim());
+                    out.push_str("](");
+                    if href.starts_with('/') {
+                        out.push_str(BASE_URL);
+                    }
+                    out.push_str(href);
+                    out.push(')');
+                }
+                None => out.push_str(&text),
+            }
+        }
+        "br" => out.push_str("  \n"),
+        "script" | "style" => (),
+        _ => render_inline(el, out),
+    }
+}
+
+fn push_collapsed(text: &str, out: &mut String) {
+    let mut last_was_space = out.ends_with([' ', '\n']);
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                out.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            out.push(c);
+            last_was_space = false;
+        }
+    }
+}
+
+fn push_code_span(code: &str, out: &mut String) {
+    if code.contains('`') {
+        out.push_str("`` ");
+        out.push_str(code);
+        out.push_str(" ``");
+    } else {
+        out.push('`');
+        out.push_str(code);
+        out.push('`');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{answers, examples, to_markdown, verdict};
+
+    // trimmed from https://adventofcode.com/2022/day/1 once part 1 was solved
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<head><title>Day 1 - Advent of Code 2022</title></head>
+<body>
+<main>
+<article class="day-desc"><h2>--- Day 1: Calorie Counting ---</h2><p>Santa's reindeer typically eat regular reindeer food, but they need a lot of <a href="/2022/day/1" target="_blank">magical energy</a> to deliver presents on Christmas.</p>
+<p>For example, suppose the Elves finish writing their items' <em>Calories</em> into the following list:</p>
+<pre><code>1000
+2000
+3000
+
+4000
+</code></pre>
+<p>This list represents the Calories of the food carried by the Elves:</p>
+<ul>
+<li>The first Elf is carrying food with <code>1000</code>, <code>2000</code>, and <code>3000</code> Calories.</li>
+<li>The second Elf is carrying one food item with <code>4000</code> Calories.</li>
+</ul>
+<p>In the example above, this is <em>24000</em> Calories (carried by the first Elf). <em>How many total Calories is that Elf carrying?</em> The answer is <code><em>24000</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>69836</code>.</p><article class="day-desc"><h2 id="part2">--- Part Two ---</h2><p>To avoid this unacceptable situation, the Elves would instead like to know the total Calories carried by the <em>top three</em> Elves.</p>
+</article>
+<form method="post" action="1/answer"><input type="hidden" name="level" value="2"/><p>Answer: <input type="text" name="answer" autocomplete="off"/> <input type="submit" value="[Submit]"/></p></form>
+</main>
+</body>
+</html>
+"#;
+
+    #[test]
+    fn markdown() {
+        let expected = "\
+## --- Day 1: Calorie Counting ---
+
+Santa's reindeer typically eat regular reindeer food, but they need a lot of [magical energy](https://adventofcode.com/2022/day/1) to deliver presents on Christmas.
+
+For example, suppose the Elves finish writing their items' *Calories* into the following list:
+
+```
+1000
+2000
+3000
+
+4000
+```
+
+This list represents the Calories of the food carried by the Elves:
+
+- The first Elf is carrying food with `1000`, `2000`, and `3000` Calories.
+- The second Elf is carrying one food item with `4000` Calories.
+
+In the example above, this is *24000* Calories (carried by the first Elf). *How many total Calories is that Elf carrying?* The answer is **`24000`**.
+
+## --- Part Two ---
+
+To avoid this unacceptable situation, the Elves would instead like to know the total Calories carried by the *top three* Elves.
+";
+        assert_eq!(to_markdown(PAGE).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn example_after_for_example() {
+        assert_eq!(examples(PAGE), ["1000\n2000\n3000\n\n4000\n"]);
+    }
+
+    #[test]
+    fn only_introduced_examples() {
+        let page = r#"<article class="day-desc">
+<p>Here is an example:</p>
+<pre><code>#.#</code></pre>
+<pre><code>unrelated</code></pre>
+<p>The rules apply to the following list:</p>
+<pre><code>also unrelated
+</code></pre>
+<p>With the larger example from before:</p>
+<pre><code>#.#
+</code></pre>
+<p>Another example:</p>
+<pre><code>
+</code></pre>
+</article>"#;
+        assert_eq!(examples(page), ["#.#\n"]);
+    }
+
+    #[test]
+    fn answer_of_part_1() {
+        assert_eq!(answers(PAGE), ["69836"]);
+    }
+
+    #[test]
+    fn answers_of_both_parts() {
+        let page = r#"<main>
+<article class="day-desc"><h2>--- Day 25: Full of Hot Air ---</h2></article>
+<p>Your puzzle answer was <code>2=-1=0</code>.</p><article class="day-desc"><h2 id="part2">--- Part Two ---</h2></article>
+<p>Your puzzle answer was <code> 42 </code>.</p><p class="day-success">Both parts of this puzzle are complete! They provide two gold stars: **</p>
+</main>"#;
+        assert_eq!(answers(page), ["2=-1=0", "42"]);
+        assert!(answers("<main><p>Answer: <code>1</code></p></main>").is_empty());
+    }
+
+    // the answer pages wrap the message in the only `article` of `main`
+    fn answer_page(message: &str) -> String {
+        format!("<html><body><main>\n<article><p>{message}</p></article>\n</main></body></html>")
+    }
+
+    #[test]
+    fn verdicts() {
+        let correct = answer_page(
+            "That's the right answer!  You are <span class=\"day-success\">one gold star</span> \
+             closer to collecting enough star fruit. <a href=\"/2023/day/1#part2\">[Continue to Part Two]</a>",
+        );
+        assert_eq!(
+            verdict(&correct),
+            "correct\nThat's the right answer! You are one gold star closer to collecting enough \
+             star fruit. [Continue to Part Two]\n"
+        );
+
+        let too_high = answer_page(
+            "That's not the right answer; your answer is too high.  If you're stuck, make sure \
+             you're using the full input data.  Please wait one minute before trying again. \
+             <a href=\"/2023/day/1\">[Return to Day 1]</a>",
+        );
+        assert!(verdict(&too_high).starts_with("too high\nThat's not the right answer; "));
+
+        let too_low = answer_page(
+            "That's not the right answer; your answer is too low.  Please wait one minute before \
+             trying again. <a href=\"/2023/day/1\">[Return to Day 1]</a>",
+        );
+        assert!(verdict(&too_low).starts_with("too low\n"));
+
+        let incorrect = answer_page(
+            "That's not the right answer.  If you're stuck, make sure you're using the full input \
+             data.  Please wait one minute before trying again. <a href=\"/2023/day/1\">[Return to Day 1]</a>",
+        );
+        assert!(verdict(&incorrect).starts_with("incorrect\n"));
+
+        let wait = answer_page(
+            "You gave an answer too recently; you have to wait after submitting an answer before \
+             trying again.  You have 37s left to wait. <a href=\"/2023/day/1\">[Return to Day 1]</a>",
+        );
+        assert!(verdict(&wait).starts_with("wait\n"));
+        assert!(verdict(&wait).contains(" You have 37s left to wait. "));
+
+        let wrong_level = answer_page(
+            "You don't seem to be solving the right level.  Did you already complete it? \
+             <a href=\"/2023/day/1\">[Return to Day 1]</a>",
+        );
+        assert!(verdict(&wrong_level).starts_with("wrong level\n"));
+    }
+
+    #[test]
+    fn unknown_verdict() {
+        assert_eq!(
+            verdict("<html><body><main></main></body></html>"),
+            "unknown\n\n"
+        );
+        assert_eq!(
+            verdict(&answer_page("Something else entirely.")),
+            "unknown\nSomething else entirely.\n"
+        );
+    }
+
+    #[test]
+    fn no_description() {
+        assert_eq!(to_markdown("<html><body><main></main></body></html>"), None);
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt::Write, io};
+
+use serde::Serialize;
+
+use crate::{cache, config::Config, html, web};
+
+#[derive(Debug, Serialize)]
+pub struct Stars {
+    pub year: u32,
+    pub total: u32,
+    pub days: Vec<DayStars>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayStars {
+    pub day: u8,
+    pub stars: u8,
+}
+
+// Downloads the calendar of `year` and caches the stars of each day both as text and as JSON
+pub fn download(config: &Config, year: u32) -> io::Result<()> {
+    let page = web::download_calendar(year, config.session_token())?;
+    let stars = parse(year, &page);
+    if stars.days.is_empty() {
+        let message = format!("No calendar found for {}", year);
+        log::error!("{}", message);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+
+    let json = serde_json::to_string_pretty(&stars).expect("Stars are serializable");
+    cache::write_atomically(&config.cached_year_stars(year), render(&stars).as_bytes())?;
+    cache::write_atomically(
+        &config.cached_year_stars_json(year),
+        (json + "\n").as_bytes(),
+    )
+}
+
+// Each released day is a link with the `calendar-dayN` class, plus `calendar-complete` (one star)
+// or `calendar-verycomplete` (two stars)
+pub fn parse(year: u32, page: &str) -> Stars {
+    let document = html::parse(page);
+    let mut days: Vec<DayStars> = document
+        .find_all(|el| el.name == "a" && calendar_day(el).is_some())
+        .into_iter()
+        .filter_map(|el| {
+            let stars = if el.has_class("calendar-verycomplete") {
+                2
+            } else if el.has_class("calendar-complete") {
+                1
+            } else {
+                0
+            };
+
+            Some(DayStars {
+                day: calendar_day(el)?,
+                stars,
+            })
+        })
+        .collect();
+
+    days.sort_by_key(|day| day.day);
+    days.dedup_by_key(|day| day.day);
+    Stars {
+        year,
+        total: days.iter().map(|day| day.stars as u32).sum(),
+        days,
+    }
+}
+
+fn calendar_day(el: &html::Element) -> Option<u8> {
+    el.attr("class")?
+        .split_ascii_whitespace()
+        .find_map(|class| class.strip_prefix("calendar-day")?.parse().ok())
+}
+
+// one `day stars` line per day
+pub fn render(stars: &Stars) -> String {
+    let mut out = String::new();
+    for day in &stars.days {
+        let _ = writeln!(out, "{} {}", day.day, day.stars);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, render};
+
+    // trimmed from https://adventofcode.com/2022 while day 11 was the latest
+    const PAGE: &str = r#"<main>
+<pre class="calendar"><span aria-hidden="true" class="calendar-day12">                       <span class="calendar-day">12</span></span>
+<a aria-label="Day 11" href="/2022/day/11" class="calendar-day11"><span class="calendar-day">11</span></a>
+<a aria-label="Day 10, one star" href="/2022/day/10" class="calendar-day10 calendar-complete"><span class="calendar-day">10</span> <span class="calendar-mark-complete">*</span><span class="calendar-mark-verycomplete">*</span></a>
+<a aria-label="Day 2, two stars" href="/2022/day/2" class="calendar-day2 calendar-verycomplete"><span class="calendar-day"> 2</span> <span class="calendar-mark-complete">*</span><span class="calendar-mark-verycomplete">*</span></a>
+<a aria-label="Day 1, two stars" href="/2022/day/1" class="calendar-day1 calendar-verycomplete"><span class="calendar-day"> 1</span> <span class="calendar-mark-complete">*</span><span class="calendar-mark-verycomplete">*</span></a>
+</pre>
+</main>"#;
+
+    #[test]
+    fn calendar_stars() {
+        let stars = parse(2022, PAGE);
+        assert_eq!(stars.year, 2022);
+        assert_eq!(stars.total, 5);
+        assert_eq!(render(&stars), "1 2\n2 2\n10 1\n11 0\n");
+    }
+
+    #[test]
+    fn no_calendar() {
+        assert!(parse(2022, "<main><p>Please log in.</p></main>")
+            .days
+            .is_empty());
+    }
+}
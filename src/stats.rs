@@ -0,0 +1,180 @@
+use std::{fmt::Write, io};
+
+use serde::Serialize;
+
+use crate::{cache, config::Config, html, web};
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub year: u32,
+    pub days: Vec<DayStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DayStats {
+    pub day: u8,
+    pub part1: Option<PartStats>,
+    pub part2: Option<PartStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartStats {
+    // as shown by AoC, `>24h` once the day is over
+    pub time: String,
+    pub rank: Option<u64>,
+    // the global leaderboard (and its scores) was discontinued in 2025
+    pub score: Option<u64>,
+}
+
+// Downloads the personal leaderboard of `year` and caches it both as a table and as JSON
+pub fn download(config: &Config, year: u32) -> io::Result<()> {
+    let page = web::download_personal_stats(year, config.session_token())?;
+    let stats = match parse(year, &page) {
+        Some(stats) => stats,
+        None => {
+            let message = format!("No personal stats found for {}", year);
+            log::error!("{}", message);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&stats).expect("Stats are serializable");
+    cache::write_atomically(&config.cached_year_stats(year), render(&stats).as_bytes())?;
+    cache::write_atomically(
+        &config.cached_year_stats_json(year),
+        (json + "\n").as_bytes(),
+    )
+}
+
+// The stats are a `pre` block with one line per solved day (most recent first):
+// `day time rank score time rank score`, part 2 being `-` while unsolved
+pub fn parse(year: u32, page: &str) -> Option<Stats> {
+    let document = html::parse(page);
+    let article = *document.find_all(|el| el.name == "article").first()?;
+
+    let mut days = Vec::new();
+    for pre in article.find_all(|el| el.name == "pre") {
+        for line in pre.text().lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(day) = fields.first().and_then(|day| day.parse::<u8>().ok()) else {
+                continue;
+            };
+
+            let parts = &fields[1..];
+            if parts.is_empty() || !parts.len().is_multiple_of(2) {
+                continue;
+            }
+
+            let (part1, part2) = parts.split_at(parts.len() / 2);
+            days.push(DayStats {
+                day,
+                part1: parse_part(part1),
+                part2: parse_part(part2),
+            });
+        }
+    }
+
+    days.sort_by_key(|stats| stats.day);
+    Some(Stats { year, days })
+}
+
+fn parse_part(fields: &[&str]) -> Option<PartStats> {
+    let time = fields.first().filter(|&&time| time != "-")?;
+    Some(PartStats {
+        time: time.to_string(),
+        rank: fields.get(1).and_then(|rank| rank.parse().ok()),
+        score: fields.get(2).and_then(|score| score.parse().ok()),
+    })
+}
+
+pub fn render(stats: &Stats) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Day  {:>10}  {:>6}  {:>5}  {:>10}  {:>6}  {:>5}",
+        "Part 1", "Rank", "Score", "Part 2", "Rank", "Score"
+    );
+    for day in &stats.days {
+        let _ = write!(out, "{:>3}", day.day);
+        for part in [&day.part1, &day.part2] {
+            let (time, rank, score) = match part {
+                Some(part) => (
+                    part.time.as_str(),
+                    part.rank.map_or("-".into(), |rank| rank.to_string()),
+                    part.score.map_or("-".into(), |score| score.to_string()),
+                ),
+                None => ("-", "-".into(), "-".into()),
+            };
+
+            let _ = write!(out, "  {:>10}  {:>6}  {:>5}", time, rank, score);
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    // trimmed from https://adventofcode.com/2022/leaderboard/self
+    const PAGE: &str = r#"<main>
+<article><p>These are your personal leaderboard statistics. <em>Rank</em> is your position on that leaderboard.</p>
+<pre><span class="leaderboard-daydesc-first">      --------Part 1--------   </span><span class="leaderboard-daydesc-both">--------Part 2--------</span>
+Day   <span class="leaderboard-daydesc-first">    Time    Rank  Score</span>   <span class="leaderboard-daydesc-both">    Time    Rank  Score</span>
+  3   00:12:34    1234      0   00:20:00    1500      0
+  2       &gt;24h   50000      0          -       -      -
+  1   00:01:02      42     59   00:02:03      17     84
+</pre>
+</article>
+</main>"#;
+
+    #[test]
+    fn personal_stats() {
+        let stats = parse(2022, PAGE).unwrap();
+        assert_eq!(stats.year, 2022);
+        assert_eq!(
+            stats.days.iter().map(|day| day.day).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+
+        let day1 = &stats.days[0];
+        let part1 = day1.part1.as_ref().unwrap();
+        assert_eq!(
+            (part1.time.as_str(), part1.rank, part1.score),
+            ("00:01:02", Some(42), Some(59))
+        );
+        let part2 = day1.part2.as_ref().unwrap();
+        assert_eq!(
+            (part2.time.as_str(), part2.rank, part2.score),
+            ("00:02:03", Some(17), Some(84))
+        );
+
+        let day2 = &stats.days[1];
+        assert_eq!(day2.part1.as_ref().unwrap().time, ">24h");
+        assert!(day2.part2.is_none());
+    }
+
+    #[test]
+    fn without_scores() {
+        let page = "<article><pre>  1   00:10:00    1000   00:20:00     900\n</pre></article>";
+        let stats = parse(2025, page).unwrap();
+        let part2 = stats.days[0].part2.as_ref().unwrap();
+        assert_eq!(
+            (part2.time.as_str(), part2.rank, part2.score),
+            ("00:20:00", Some(900), None)
+        );
+    }
+
+    #[test]
+    fn no_stats() {
+        assert!(parse(2022, "<main><p>You haven't collected any stars.</p></main>").is_none());
+        let stats = parse(
+            2022,
+            "<article><p>You haven't collected any stars.</p></article>",
+        );
+        assert!(stats.unwrap().days.is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fmt::Write, io};
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::filesystem::DayAndYear;
+
+// what happened since the filesystem was mounted, shown in `/.status`
+#[derive(Debug)]
+pub struct Status {
+    mounted_at: DateTime<Utc>,
+    downloads: u64,
+    last_error: Option<(DateTime<Utc>, String)>,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            mounted_at: Utc::now(),
+            downloads: 0,
+            last_error: None,
+        }
+    }
+}
+
+impl Status {
+    pub fn record_download(&mut self, result: &io::Result<()>) {
+        match result {
+            Ok(()) => self.downloads += 1,
+            Err(err) => self.last_error = Some((Utc::now(), err.to_string())),
+        }
+    }
+
+    pub fn render(
+        &self,
+        cache_dir: &str,
+        cached_inputs: usize,
+        next_unlock: (DayAndYear, DateTime<FixedOffset>),
+    ) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "mounted at: {}", self.mounted_at.format("%F %T UTC"));
+        let _ = writeln!(out, "cache dir: {}", cache_dir);
+        let _ = writeln!(out, "cached inputs: {}", cached_inputs);
+        let _ = writeln!(out, "downloads: {}", self.downloads);
+        match &self.last_error {
+            Some((time, err)) => {
+                let _ = writeln!(out, "last error: {}: {}", time.format("%F %T UTC"), err);
+            }
+            None => out.push_str("last error: none\n"),
+        }
+
+        let (day, time) = next_unlock;
+        let _ = writeln!(
+            out,
+            "next unlock: {}/day{:02} at {}",
+            day.year,
+            day.day,
+            time.format("%F %T %:z")
+        );
+
+        out
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    cache::{self, CachedFile, Metadata, QUARANTINE_DIR},
+    calendar::{self, Calendar},
+    clock::Clock,
+    config::Config,
+    filesystem::DayAndYear,
+    inode::DayFile,
+    prefetch::Fetcher,
+    web,
+};
+
+const REDOWNLOAD_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug)]
+pub struct Problem {
+    // relative to the cache dir
+    pub path: PathBuf,
+    pub reason: String,
+    // day of the input to download again once quarantined
+    pub input: Option<DayAndYear>,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub checked: usize,
+    pub problems: Vec<Problem>,
+    // with `repair`
+    pub quarantined: usize,
+    pub redownloaded: usize,
+    pub failed: usize,
+}
+
+// Checks every file of the cache dir, and with `repair` moves the bad ones to the quarantine dir
+// and downloads the inputs among them again
+pub fn verify(config: &Config, clock: Clock, repair: bool) -> io::Result<Report> {
+    let calendar = calendar::from_config(config);
+    let latest = calendar.last_unlocked_puzzle(clock.now());
+    let files = cache::list_files(config.cache_dir())?;
+
+    let mut report = Report::default();
+    for relative in files {
+        report.checked += 1;
+        let path = config.cache_dir().join(&relative);
+        if let Err(reason) = check(&calendar, latest, &path, &relative) {
+            let input = match CachedFile::from_path(&relative) {
+                Some(CachedFile::Day(day, DayFile::Input)) if exists(&calendar, latest, day) => {
+                    Some(day)
+                }
+                _ => None,
+            };
+
+            report.problems.push(Problem {
+                path: relative,
+                reason,
+                input,
+            });
+        }
+    }
+
+    if !repair {
+        return Ok(report);
+    }
+
+    let quarantine = config.cache_dir().join(QUARANTINE_DIR);
+    let mut fetcher = Fetcher::new(REDOWNLOAD_DELAY);
+    for problem in &report.problems {
+        // the metadata of a quarantined input already followed it
+        if !config.cache_dir().join(&problem.path).exists() {
+            continue;
+        }
+
+        if let Err(err) = quarantine_file(config.cache_dir(), &quarantine, &problem.path) {
+            eprintln!("{}: could not quarantine: {}", problem.path.display(), err);
+            report.failed += 1;
+            continue;
+        }
+
+        report.quarantined += 1;
+        if let Some(day) = problem.input {
+            // its metadata describes the quarantined version
+            let input_path = config.cached_day_input(day);
+            let meta_path = cache::metadata_path(&problem.path);
+            if config.cache_dir().join(&meta_path).exists() {
+                let _ = quarantine_file(config.cache_dir(), &quarantine, &meta_path);
+            }
+
+            fetcher.fetch(day, "input", &input_path, || {
+                web::download_input(
+                    day,
+                    &input_path,
+                    config.session_token(),
+                    config.username(),
+                    config.compress(),
+                )
+            });
+        }
+    }
+
+    report.redownloaded = fetcher.summary().downloaded;
+    report.failed += fetcher.summary().failed;
+    Ok(report)
+}
+
+fn exists(calendar: &Calendar, latest: DayAndYear, day: DayAndYear) -> bool {
+    calendar
+        .released_days(day.year, latest)
+        .is_some_and(|released| day.day <= released)
+}
+
+fn check(
+    calendar: &Calendar,
+    latest: DayAndYear,
+    path: &Path,
+    relative: &Path,
+) -> Result<(), String> {
+    let file = match CachedFile::from_path(relative) {
+        Some(file) => file,
+        None => return Err("unexpected name".to_string()),
+    };
+
+    match file.day() {
+        Some(day) if !exists(calendar, latest, day) => {
+            return Err(format!("no released puzzle {}/day{:02}", day.year, day.day))
+        }
+        None if calendar.released_days(file.year(), latest).is_none() => {
+            return Err(format!("no released event in {}", file.year()))
+        }
+        _ => (),
+    }
+
+    match file {
+        CachedFile::Day(_, DayFile::Input) => {
+            let data = cache::read(path).map_err(|err| err.to_string())?;
+            if let Err(reason) = web::validate_body(&data) {
+                return Err(reason.to_string());
+            }
+
+            match Metadata::load(path) {
+                Some(metadata) if metadata.sha256 != cache::sha256(&data) => {
+                    Err("content doesn't match its metadata".to_string())
+                }
+                _ => Ok(()),
+            }
+        }
+        CachedFile::InputMetadata(_) => {
+            // `dayN.txt.meta` => `dayN.txt`
+            let input = path.with_extension("");
+            if !input.exists() {
+                Err("metadata of a missing input".to_string())
+            } else if Metadata::load(&input).is_none() {
+                Err("invalid metadata".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+// moves `relative` from the cache dir to the same place under `quarantine`
+fn quarantine_file(cache_dir: &Path, quarantine: &Path, relative: &Path) -> io::Result<()> {
+    let target = quarantine.join(relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::rename(cache_dir.join(relative), target)
+}
@@ -1,24 +1,36 @@
-use std::{
-    fs::{self, File},
-    io::{self},
-    path::Path,
-    time::Duration,
-};
+use std::{io, path::Path, time::Duration};
+
+use std::collections::BTreeMap;
 
-use bytes::Bytes;
 use reqwest::blocking::{Client, Response};
 
-use crate::filesystem::DayAndYear;
+use crate::{
+    cache::{self, Metadata},
+    filesystem::DayAndYear,
+};
 
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 const USER_AGENT: &str = "aoc-fs (https://github.com/guy_732/aoc-fs by guilhem.chaillou@gmail.com)";
-const BASE_URL: &str = "https://adventofcode.com";
+pub const BASE_URL: &str = "https://adventofcode.com";
+// response headers kept in the metadata of downloaded inputs
+const RECORDED_HEADERS: [&str; 5] = [
+    "date",
+    "content-type",
+    "content-length",
+    "last-modified",
+    "etag",
+];
 
-pub fn download_input(day: DayAndYear, save_path: &Path, session: &str) -> io::Result<()> {
-    let url = format!("{BASE_URL}/{}/day/{}/input", day.year, day.day);
+// the filesystem reports these as ENETDOWN, the message is kept for `/.status`
+fn network_error(message: String) -> io::Error {
+    log::error!("{}", message);
+    io::Error::new(io::ErrorKind::NetworkDown, message)
+}
+
+fn get(url: &str, session: &str) -> io::Result<Response> {
     let client = Client::new();
     let req = client
-        .get(&url)
+        .get(url)
         .header("User-Agent", USER_AGENT)
         .header("Cookie", format!("session={}", session))
         .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
@@ -27,37 +39,212 @@ pub fn download_input(day: DayAndYear, save_path: &Path, session: &str) -> io::R
 
     let response = match client.execute(req) {
         Err(e) => {
-            log::error!("Request failed for {:?}: {}", &url, e);
-            return Err(io::Error::from_raw_os_error(libc::ENETDOWN));
+            return Err(network_error(format!(
+                "Request failed for {:?}: {}",
+                url, e
+            )))
         }
-
         Ok(response) => response,
     };
 
-    let response = match response.error_for_status() {
-        Ok(r) => r,
+    match response.error_for_status() {
+        Ok(r) => Ok(r),
+        Err(e) => Err(network_error(format!(
+            "Request failed for {:?}: {}",
+            url, e
+        ))),
+    }
+}
+
+// `account` is only recorded in the metadata written next to the input
+pub fn download_input(
+    day: DayAndYear,
+    save_path: &Path,
+    session: &str,
+    account: &str,
+    compress: bool,
+) -> io::Result<()> {
+    let url = format!("{BASE_URL}/{}/day/{}/input", day.year, day.day);
+    let response = get(&url, session)?;
+    let status = response.status().as_u16();
+    let headers: BTreeMap<String, String> = RECORDED_HEADERS
+        .iter()
+        .filter_map(|&name| {
+            let value = response.headers().get(name)?.to_str().ok()?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect();
+
+    let data = checked_body(&url, response)?;
+    cache::write(save_path, &data, compress)?;
+    let metadata = Metadata::new(account, status, headers, &data);
+    if let Err(err) = metadata.save(save_path) {
+        // the input itself is fine, only its origin is unknown
+        log::warn!("Could not save the metadata of {:?}: {}", save_path, err);
+    }
+
+    Ok(())
+}
+
+pub fn download_leaderboard(
+    id: u64,
+    year: u32,
+    save_path: &Path,
+    session: &str,
+    compress: bool,
+) -> io::Result<()> {
+    let url = format!("{BASE_URL}/{year}/leaderboard/private/view/{id}.json");
+    save_response(&url, save_path, session, compress)
+}
+
+// the body is fully received and checked before replacing the previous version, if any
+fn save_response(url: &str, save_path: &Path, session: &str, compress: bool) -> io::Result<()> {
+    let data = checked_body(url, get(url, session)?)?;
+    cache::write(save_path, &data, compress)
+}
+
+fn checked_body(url: &str, response: Response) -> io::Result<Vec<u8>> {
+    let data = match response.bytes() {
+        Ok(data) => data.to_vec(),
         Err(e) => {
-            log::error!("Request failed for {:?}: {}", &url, e);
-            return Err(io::Error::from_raw_os_error(libc::ENETDOWN));
+            return Err(network_error(format!(
+                "Failed to read response body of {:?}: {}",
+                url, e
+            )))
         }
     };
 
-    let writer = File::create(save_path)?;
-    match write_response(response, writer) {
-        Ok(()) => (),
-        Err(err) => {
-            log::error!("Failed to write response to file: {}", err);
-            // try to clean up the mess
-            let _ = fs::remove_file(save_path);
-            return Err(err);
+    match validate_body(&data) {
+        Ok(()) => Ok(data),
+        Err(reason) => {
+            let message = format!("Invalid response for {:?}: {}", url, reason);
+            log::error!("{}", message);
+            Err(io::Error::new(io::ErrorKind::InvalidData, message))
         }
     }
+}
 
-    Ok(())
+// inputs and leaderboards are never HTML, getting a page means the session expired or the request
+// was redirected (the "before it unlocks" message is plain text but must not be cached either)
+pub fn validate_body(data: &[u8]) -> Result<(), &'static str> {
+    let start = String::from_utf8_lossy(&data[..data.len().min(512)]).to_ascii_lowercase();
+    // some inputs are made of `<>^v` and may start with `<`, only a document start is HTML
+    let trimmed = start.trim_start();
+    if data.is_empty() {
+        Err("empty body")
+    } else if trimmed.starts_with("<!doctype") || trimmed.starts_with("<html") {
+        Err("got an HTML page")
+    } else if start.contains("before it unlocks") {
+        Err("the puzzle is not unlocked yet")
+    } else if start.contains("please log in") {
+        Err("not logged in, the session token may have expired")
+    } else {
+        Ok(())
+    }
+}
+
+pub fn puzzle_url(day: DayAndYear) -> String {
+    format!("{BASE_URL}/{}/day/{}", day.year, day.day)
+}
+
+pub fn download_puzzle_page(day: DayAndYear, session: &str) -> io::Result<String> {
+    download_page(&puzzle_url(day), session)
+}
+
+pub fn download_personal_stats(year: u32, session: &str) -> io::Result<String> {
+    download_page(&format!("{BASE_URL}/{year}/leaderboard/self"), session)
+}
+
+pub fn download_calendar(year: u32, session: &str) -> io::Result<String> {
+    download_page(&format!("{BASE_URL}/{year}"), session)
 }
 
-fn write_response<W: io::Write>(response: Response, mut writer: W) -> io::Result<()> {
-    let data = response.bytes().unwrap_or(Bytes::from_static(b""));
-    writer.write_all(&data)?;
-    writer.flush()
+pub fn download_events_page(session: &str) -> io::Result<String> {
+    download_page(&format!("{BASE_URL}/events"), session)
+}
+
+fn download_page(url: &str, session: &str) -> io::Result<String> {
+    match get(url, session)?.text() {
+        Ok(page) => Ok(page),
+        Err(e) => Err(network_error(format!(
+            "Failed to read response body of {:?}: {}",
+            url, e
+        ))),
+    }
+}
+
+pub fn submit_answer(day: DayAndYear, part: u8, answer: &str, session: &str) -> io::Result<String> {
+    let url = format!("{BASE_URL}/{}/day/{}/answer", day.year, day.day);
+    let client = Client::new();
+    let req = client
+        .post(&url)
+        .header("User-Agent", USER_AGENT)
+        .header("Cookie", format!("session={}", session))
+        .form(&[("level", part.to_string().as_str()), ("answer", answer)])
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .expect("Could not build web request");
+
+    let response = match client.execute(req).and_then(|r| r.error_for_status()) {
+        Ok(response) => response,
+        Err(e) => {
+            return Err(network_error(format!(
+                "Request failed for {:?}: {}",
+                &url, e
+            )))
+        }
+    };
+
+    match response.text() {
+        Ok(page) => Ok(page),
+        Err(e) => Err(network_error(format!(
+            "Failed to read response body of {:?}: {}",
+            &url, e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_body;
+
+    #[test]
+    fn empty_body() {
+        assert_eq!(validate_body(b""), Err("empty body"));
+    }
+
+    #[test]
+    fn html_pages() {
+        let page = b"<!DOCTYPE html>\n<html lang=\"en-us\">\n<head>";
+        assert_eq!(validate_body(page), Err("got an HTML page"));
+        assert_eq!(validate_body(b"\n  <html><body>"), Err("got an HTML page"));
+    }
+
+    #[test]
+    fn not_unlocked_yet() {
+        let body = b"Please don't repeatedly request this endpoint before it unlocks! \
+            The calendar countdown is synchronized with the server time; \
+            the link will be enabled on the calendar the instant this puzzle becomes available.\n";
+        assert_eq!(validate_body(body), Err("the puzzle is not unlocked yet"));
+    }
+
+    #[test]
+    fn logged_out() {
+        let body = b"Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+        assert_eq!(
+            validate_body(body),
+            Err("not logged in, the session token may have expired")
+        );
+    }
+
+    #[test]
+    fn inputs_starting_with_angle_brackets() {
+        assert_eq!(validate_body(b"<v>^<<>^vv>\n^^<>v<\n"), Ok(()));
+        assert_eq!(validate_body(b"<<^^>>vv\n"), Ok(()));
+    }
+
+    #[test]
+    fn regular_input() {
+        assert_eq!(validate_body(b"3   4\n4   3\n2   5\n"), Ok(()));
+    }
 }